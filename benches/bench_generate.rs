@@ -1,18 +1,27 @@
 use criterion::{Criterion, criterion_group, criterion_main};
+use genix_lib::generate::{GeneratorConfig, Style};
 
 fn bench_random_generate(c: &mut Criterion) {
+    let mut generator = GeneratorConfig::new(Style::Random)
+        .length(128)
+        .build()
+        .unwrap();
     c.bench_function("generate_random_128", |b| {
         b.iter(|| {
             // call binary via library
-            let _ = genix_lib::generate::generate_many("random", 128, 1, None, false, None);
+            let _ = generator.generate_one();
         })
     });
 }
 
 fn bench_passphrase_generate(c: &mut Criterion) {
+    let mut generator = GeneratorConfig::new(Style::Passphrase)
+        .length(4)
+        .build()
+        .unwrap();
     c.bench_function("generate_passphrase_4", |b| {
         b.iter(|| {
-            let _ = genix_lib::generate::generate_many("passphrase", 4, 1, None, false, None);
+            let _ = generator.generate_one();
         })
     });
 }
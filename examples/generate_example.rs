@@ -1,7 +1,12 @@
+use genix_lib::generate::{GeneratorConfig, Style};
+
 fn main() {
     // A tiny example showing how to call the library directly.
-    let results = genix_lib::generate::generate_many("random", 24, 3, None, false, None).unwrap();
-    for r in results {
-        println!("{}", r);
+    let mut generator = GeneratorConfig::new(Style::Random)
+        .length(24)
+        .build()
+        .unwrap();
+    for r in generator.generate_iter().take(3) {
+        println!("{}", r.unwrap());
     }
 }
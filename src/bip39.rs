@@ -0,0 +1,188 @@
+//! BIP39 mnemonic generation and validation.
+//!
+//! A mnemonic encodes `ENT` random bits (128 to 256, a multiple of 32) plus an
+//! `ENT / 32`-bit checksum taken from the start of `SHA-256(entropy)`. The
+//! concatenated bits are split into 11-bit indices into the 2048-word English
+//! list, giving 12, 15, 18, 21 or 24 words. The list is embedded from
+//! `assets/wordlists/bip39_english.txt`.
+//!
+//! Only the checksum is computed here; seed derivation (PBKDF2 with a
+//! passphrase) is out of scope.
+
+use rand::{CryptoRng, RngCore};
+use sha2::{Digest, Sha256};
+
+use crate::error::GenixError;
+
+const ENGLISH: &str = include_str!("../assets/wordlists/bip39_english.txt");
+
+/// Word counts allowed by BIP39.
+pub const WORD_COUNTS: [usize; 5] = [12, 15, 18, 21, 24];
+
+/// The 2048 words of the English list, in index order.
+pub fn english_words() -> Vec<&'static str> {
+    ENGLISH
+        .lines()
+        .map(str::trim)
+        .filter(|w| !w.is_empty())
+        .collect()
+}
+
+/// Random bits encoded by a mnemonic of `words` words (`words * 32 / 3`).
+///
+/// # Errors
+/// Returns `GenixError::Bip39WordCount` unless `words` is in `WORD_COUNTS`.
+pub fn entropy_bits(words: usize) -> Result<usize, GenixError> {
+    if WORD_COUNTS.contains(&words) {
+        Ok(words * 32 / 3)
+    } else {
+        Err(GenixError::Bip39WordCount(words))
+    }
+}
+
+/// Encode `entropy` (16 to 32 bytes, a multiple of 4) as a mnemonic.
+///
+/// # Errors
+/// Returns `GenixError::Bip39WordCount` for any other entropy length.
+pub fn mnemonic_from_entropy(entropy: &[u8]) -> Result<String, GenixError> {
+    let words = entropy.len() * 3 / 4;
+    if !entropy.len().is_multiple_of(4) || entropy_bits(words).is_err() {
+        return Err(GenixError::Bip39WordCount(words));
+    }
+    let checksum = Sha256::digest(entropy);
+    let mut bits = entropy.to_vec();
+    bits.push(checksum[0]);
+    let list = english_words();
+    let phrase: Vec<&str> = (0..words)
+        .map(|i| {
+            let index = (0..11).fold(0usize, |acc, j| (acc << 1) | bit(&bits, i * 11 + j));
+            list[index]
+        })
+        .collect();
+    Ok(phrase.join(" "))
+}
+
+/// Generate a `words`-word mnemonic from `rng`.
+///
+/// # Errors
+/// Returns `GenixError::Bip39WordCount` unless `words` is in `WORD_COUNTS`.
+pub fn generate_mnemonic<R: RngCore + CryptoRng>(
+    rng: &mut R,
+    words: usize,
+) -> Result<String, GenixError> {
+    let mut entropy = vec![0u8; entropy_bits(words)? / 8];
+    rng.fill_bytes(&mut entropy);
+    mnemonic_from_entropy(&entropy)
+}
+
+/// Check word count, word membership and checksum of `phrase`, returning the
+/// number of random bits it encodes (128 to 256).
+///
+/// Words may be separated by any whitespace and are matched case-insensitively.
+///
+/// # Errors
+/// Returns `GenixError::Bip39WordCount` for a bad word count and
+/// `GenixError::InvalidMnemonic` for unknown words or a checksum mismatch.
+pub fn validate_mnemonic(phrase: &str) -> Result<usize, GenixError> {
+    let words: Vec<String> = phrase.split_whitespace().map(str::to_lowercase).collect();
+    let ent = entropy_bits(words.len())?;
+    let list = english_words();
+    let mut indices = Vec::with_capacity(words.len());
+    let mut unknown = Vec::new();
+    for word in &words {
+        match list.binary_search(&word.as_str()) {
+            Ok(index) => indices.push(index),
+            Err(_) => unknown.push(word.as_str()),
+        }
+    }
+    if !unknown.is_empty() {
+        return Err(GenixError::InvalidMnemonic(format!(
+            "not in the English list: {}",
+            unknown.join(", ")
+        )));
+    }
+
+    let mut bits = vec![0u8; (words.len() * 11).div_ceil(8)];
+    for (i, index) in indices.iter().enumerate() {
+        for j in 0..11 {
+            if index >> (10 - j) & 1 == 1 {
+                let pos = i * 11 + j;
+                bits[pos / 8] |= 0x80 >> (pos % 8);
+            }
+        }
+    }
+    let entropy = &bits[..ent / 8];
+    let expected = Sha256::digest(entropy)[0] >> (8 - ent / 32);
+    let actual = (0..ent / 32).fold(0u8, |acc, j| (acc << 1) | bit(&bits, ent + j) as u8);
+    if expected != actual {
+        return Err(GenixError::InvalidMnemonic("checksum mismatch".into()));
+    }
+    Ok(ent)
+}
+
+/// Bit `pos` of `bytes`, most significant bit first.
+fn bit(bytes: &[u8], pos: usize) -> usize {
+    usize::from(bytes[pos / 8] >> (7 - pos % 8) & 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate::seeded_rng;
+
+    #[test]
+    fn test_english_list_is_sorted_and_complete() {
+        let list = english_words();
+        assert_eq!(list.len(), 2048);
+        assert!(list.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[test]
+    fn test_reference_vectors() {
+        // from the BIP39 reference test vectors
+        let zero = mnemonic_from_entropy(&[0u8; 16]).unwrap();
+        assert_eq!(
+            zero,
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about"
+        );
+        let ones = mnemonic_from_entropy(&[0xffu8; 32]).unwrap();
+        assert_eq!(
+            ones,
+            "zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo vote"
+        );
+        assert_eq!(validate_mnemonic(&zero).unwrap(), 128);
+        assert_eq!(validate_mnemonic(&ones).unwrap(), 256);
+    }
+
+    #[test]
+    fn test_generated_mnemonics_validate() {
+        let mut rng = seeded_rng("39").unwrap();
+        for words in WORD_COUNTS {
+            let phrase = generate_mnemonic(&mut rng, words).unwrap();
+            assert_eq!(phrase.split(' ').count(), words);
+            assert_eq!(validate_mnemonic(&phrase).unwrap(), words * 32 / 3);
+        }
+        assert!(matches!(
+            generate_mnemonic(&mut rng, 13),
+            Err(GenixError::Bip39WordCount(13))
+        ));
+    }
+
+    #[test]
+    fn test_validate_rejects_bad_phrases() {
+        let bad_checksum = ["abandon"; 12].join(" ");
+        assert!(matches!(
+            validate_mnemonic(&bad_checksum),
+            Err(GenixError::InvalidMnemonic(_))
+        ));
+        let unknown = format!("{} abandonx", ["abandon"; 11].join(" "));
+        assert!(matches!(
+            validate_mnemonic(&unknown),
+            Err(GenixError::InvalidMnemonic(_))
+        ));
+        assert!(matches!(
+            validate_mnemonic("abandon about"),
+            Err(GenixError::Bip39WordCount(2))
+        ));
+    }
+}
@@ -0,0 +1,423 @@
+//! Offline lookups in a Have I Been Pwned "Pwned Passwords" dump.
+//!
+//! The SHA-1 ordered-by-hash download is a text file of `HASH:COUNT` lines
+//! (40 hex digits, then how often the password appeared in breaches), sorted
+//! by hash. `BreachDb` bisects that file by byte offset, so the multi-gigabyte
+//! dump is never loaded into memory. `build_index` converts it to a compact
+//! binary index: the 8-byte `INDEX_MAGIC` followed by 24-byte records (the
+//! 20-byte hash and a big-endian `u32` count), searched one record per step.
+//! Only the SHA-1 of the password is compared; nothing leaves the machine.
+
+use std::cmp::Ordering;
+use std::fs::File;
+use std::io::{BufRead, Read, Seek, SeekFrom, Write};
+
+use sha1::{Digest, Sha1};
+
+use crate::error::GenixError;
+
+/// First bytes of an index written by `build_index`.
+pub const INDEX_MAGIC: &[u8; 8] = b"GNXHIBP1";
+/// Bytes per index record: SHA-1 hash plus `u32` count.
+const RECORD_LEN: u64 = 24;
+/// Bytes read per step when scanning the text dump for a line break.
+const CHUNK_LEN: usize = 128;
+/// Breached items `take_unbreached` skips in a row before giving up.
+pub const MAX_BREACHED_IN_A_ROW: usize = 10_000;
+
+/// SHA-1 of `password`, as indexed by Pwned Passwords.
+pub fn password_hash(password: &str) -> [u8; 20] {
+    Sha1::digest(password.as_bytes()).into()
+}
+
+/// On-disk layout of a breach database.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BreachDbFormat {
+    /// Sorted `HASH:COUNT` lines, as downloaded.
+    Text,
+    /// Binary index from `build_index`.
+    Index,
+}
+
+/// A sorted Pwned Passwords dump or index, searched in place.
+#[derive(Debug)]
+pub struct BreachDb<R = File> {
+    reader: R,
+    format: BreachDbFormat,
+    len: u64,
+}
+
+impl BreachDb<File> {
+    /// Open a text dump or binary index; the format is detected from the
+    /// first bytes.
+    ///
+    /// # Errors
+    /// Returns `GenixError::BreachDbIo` if the file cannot be opened and
+    /// `GenixError::InvalidBreachDb` if an index is truncated.
+    pub fn open(path: &str) -> Result<Self, GenixError> {
+        let file = File::open(path).map_err(|source| GenixError::BreachDbIo {
+            path: path.to_string(),
+            source,
+        })?;
+        BreachDb::from_reader(file)
+    }
+}
+
+impl<R: Read + Seek> BreachDb<R> {
+    /// Wrap any seekable reader holding a text dump or binary index.
+    ///
+    /// # Errors
+    /// Returns `GenixError::InvalidBreachDb` if the reader fails or an index
+    /// is not a whole number of records.
+    pub fn from_reader(mut reader: R) -> Result<Self, GenixError> {
+        let len = reader.seek(SeekFrom::End(0)).map_err(read_error)?;
+        let mut magic = [0u8; 8];
+        let format = if len >= magic.len() as u64 {
+            reader.seek(SeekFrom::Start(0)).map_err(read_error)?;
+            reader.read_exact(&mut magic).map_err(read_error)?;
+            if &magic == INDEX_MAGIC {
+                BreachDbFormat::Index
+            } else {
+                BreachDbFormat::Text
+            }
+        } else {
+            BreachDbFormat::Text
+        };
+        if format == BreachDbFormat::Index && !(len - magic.len() as u64).is_multiple_of(RECORD_LEN)
+        {
+            return Err(GenixError::InvalidBreachDb(
+                "index is truncated (partial record)".into(),
+            ));
+        }
+        Ok(BreachDb {
+            reader,
+            format,
+            len,
+        })
+    }
+
+    /// Whether this is a text dump or a binary index.
+    pub fn format(&self) -> BreachDbFormat {
+        self.format
+    }
+
+    /// How often `password` appears in the breach data, or `None`.
+    ///
+    /// # Errors
+    /// Returns `GenixError::InvalidBreachDb` if reading fails or a visited
+    /// line is not `HASH:COUNT`.
+    pub fn lookup(&mut self, password: &str) -> Result<Option<u64>, GenixError> {
+        self.lookup_hash(&password_hash(password))
+    }
+
+    /// Take `count` items from `items`, skipping those found in the
+    /// database. Returns the items and how many were skipped.
+    ///
+    /// # Errors
+    /// Returns the errors of `items` and `lookup`, and
+    /// `GenixError::TooManyBreached` once `MAX_BREACHED_IN_A_ROW` items in a
+    /// row are breached, e.g. when the database holds every possible item.
+    pub fn take_unbreached<I>(
+        &mut self,
+        items: I,
+        count: usize,
+    ) -> Result<(Vec<String>, usize), GenixError>
+    where
+        I: IntoIterator<Item = Result<String, GenixError>>,
+    {
+        let mut taken = Vec::with_capacity(count);
+        let mut skipped = 0;
+        let mut in_a_row = 0;
+        let mut items = items.into_iter();
+        while taken.len() < count {
+            let Some(item) = items.next() else {
+                break;
+            };
+            let item = item?;
+            if self.lookup(&item)?.is_none() {
+                taken.push(item);
+                in_a_row = 0;
+                continue;
+            }
+            skipped += 1;
+            in_a_row += 1;
+            if in_a_row == MAX_BREACHED_IN_A_ROW {
+                return Err(GenixError::TooManyBreached(in_a_row));
+            }
+        }
+        Ok((taken, skipped))
+    }
+
+    /// Like `lookup`, for a precomputed SHA-1 hash.
+    pub fn lookup_hash(&mut self, hash: &[u8; 20]) -> Result<Option<u64>, GenixError> {
+        match self.format {
+            BreachDbFormat::Text => self.lookup_text(hash),
+            BreachDbFormat::Index => self.lookup_index(hash),
+        }
+    }
+
+    /// Bisect line starts in `lo..hi`; `lo` is always a line start.
+    fn lookup_text(&mut self, hash: &[u8; 20]) -> Result<Option<u64>, GenixError> {
+        let (mut lo, mut hi) = (0, self.len);
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            let start = self.line_start_at_or_after(mid)?;
+            if start >= hi {
+                hi = mid;
+                continue;
+            }
+            let (line, end) = self.line_at(start)?;
+            let Some((line_hash, count)) = parse_line(&line)? else {
+                // blank line: step over it
+                lo = end;
+                continue;
+            };
+            match line_hash.cmp(hash) {
+                Ordering::Equal => return Ok(Some(count)),
+                Ordering::Less => lo = end,
+                Ordering::Greater => hi = start,
+            }
+        }
+        Ok(None)
+    }
+
+    fn lookup_index(&mut self, hash: &[u8; 20]) -> Result<Option<u64>, GenixError> {
+        let (mut lo, mut hi) = (0, (self.len - INDEX_MAGIC.len() as u64) / RECORD_LEN);
+        let mut record = [0u8; RECORD_LEN as usize];
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            self.reader
+                .seek(SeekFrom::Start(INDEX_MAGIC.len() as u64 + mid * RECORD_LEN))
+                .map_err(read_error)?;
+            self.reader.read_exact(&mut record).map_err(read_error)?;
+            match record[..20].cmp(&hash[..]) {
+                Ordering::Equal => {
+                    let count =
+                        u32::from_be_bytes([record[20], record[21], record[22], record[23]]);
+                    return Ok(Some(u64::from(count)));
+                }
+                Ordering::Less => lo = mid + 1,
+                Ordering::Greater => hi = mid,
+            }
+        }
+        Ok(None)
+    }
+
+    /// Up to `CHUNK_LEN` bytes starting at `pos`.
+    fn chunk_at(&mut self, pos: u64) -> Result<Vec<u8>, GenixError> {
+        self.reader.seek(SeekFrom::Start(pos)).map_err(read_error)?;
+        let mut chunk = Vec::with_capacity(CHUNK_LEN);
+        (&mut self.reader)
+            .take(CHUNK_LEN as u64)
+            .read_to_end(&mut chunk)
+            .map_err(read_error)?;
+        Ok(chunk)
+    }
+
+    /// Offset of the first line that starts at or after `pos` (or `len`).
+    fn line_start_at_or_after(&mut self, pos: u64) -> Result<u64, GenixError> {
+        if pos == 0 {
+            return Ok(0);
+        }
+        let mut at = pos - 1;
+        while at < self.len {
+            let chunk = self.chunk_at(at)?;
+            if chunk.is_empty() {
+                break;
+            }
+            if let Some(i) = chunk.iter().position(|&b| b == b'\n') {
+                return Ok(at + i as u64 + 1);
+            }
+            at += chunk.len() as u64;
+        }
+        Ok(self.len)
+    }
+
+    /// The line starting at `start` (without its line break) and the offset
+    /// of the next line.
+    fn line_at(&mut self, start: u64) -> Result<(Vec<u8>, u64), GenixError> {
+        let mut line = Vec::new();
+        let mut at = start;
+        while at < self.len {
+            let chunk = self.chunk_at(at)?;
+            if chunk.is_empty() {
+                break;
+            }
+            if let Some(i) = chunk.iter().position(|&b| b == b'\n') {
+                line.extend_from_slice(&chunk[..i]);
+                return Ok((line, at + i as u64 + 1));
+            }
+            line.extend_from_slice(&chunk);
+            at += chunk.len() as u64;
+        }
+        Ok((line, self.len))
+    }
+}
+
+/// Convert a sorted `HASH:COUNT` dump into a binary index, returning the
+/// number of records written. Counts above `u32::MAX` are saturated.
+///
+/// # Errors
+/// Returns `GenixError::InvalidBreachDb` if a line is malformed, the hashes
+/// are not strictly ascending, or reading or writing fails.
+pub fn build_index<R: BufRead, W: Write>(input: R, mut output: W) -> Result<u64, GenixError> {
+    output.write_all(INDEX_MAGIC).map_err(write_error)?;
+    let mut previous: Option<[u8; 20]> = None;
+    let mut records = 0u64;
+    for (number, line) in input.split(b'\n').enumerate() {
+        let line = line.map_err(read_error)?;
+        let Some((hash, count)) = parse_line(&line)
+            .map_err(|e| GenixError::InvalidBreachDb(format!("line {}: {}", number + 1, e)))?
+        else {
+            continue;
+        };
+        if previous.is_some_and(|previous| previous >= hash) {
+            return Err(GenixError::InvalidBreachDb(format!(
+                "line {}: hashes must be sorted and unique (use the ordered-by-hash download)",
+                number + 1
+            )));
+        }
+        let count = u32::try_from(count).unwrap_or(u32::MAX);
+        output.write_all(&hash).map_err(write_error)?;
+        output
+            .write_all(&count.to_be_bytes())
+            .map_err(write_error)?;
+        previous = Some(hash);
+        records += 1;
+    }
+    output.flush().map_err(write_error)?;
+    Ok(records)
+}
+
+/// Parse `HASH:COUNT` (trailing `\r` allowed); `None` for a blank line.
+fn parse_line(line: &[u8]) -> Result<Option<([u8; 20], u64)>, GenixError> {
+    let line = line.strip_suffix(b"\r").unwrap_or(line);
+    if line.is_empty() {
+        return Ok(None);
+    }
+    let malformed = || {
+        GenixError::InvalidBreachDb(format!(
+            "expected HASH:COUNT, got {:?}",
+            String::from_utf8_lossy(line)
+        ))
+    };
+    let text = std::str::from_utf8(line).map_err(|_| malformed())?;
+    let (hex, count) = text.split_once(':').ok_or_else(malformed)?;
+    if hex.len() != 40 {
+        return Err(malformed());
+    }
+    let mut hash = [0u8; 20];
+    for (byte, pair) in hash.iter_mut().zip(hex.as_bytes().chunks(2)) {
+        let pair = std::str::from_utf8(pair).map_err(|_| malformed())?;
+        *byte = u8::from_str_radix(pair, 16).map_err(|_| malformed())?;
+    }
+    let count = count.trim().parse().map_err(|_| malformed())?;
+    Ok(Some((hash, count)))
+}
+
+fn read_error(e: std::io::Error) -> GenixError {
+    GenixError::InvalidBreachDb(format!("read failed: {}", e))
+}
+
+fn write_error(e: std::io::Error) -> GenixError {
+    GenixError::InvalidBreachDb(format!("write failed: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    use crate::generate::{GeneratorConfig, Style, seeded_rng};
+
+    fn hex(hash: &[u8; 20]) -> String {
+        hash.iter().map(|b| format!("{:02X}", b)).collect()
+    }
+
+    /// A sorted dump of `passwords` with counts 1, 2, ...
+    fn dump(passwords: &[&str]) -> Vec<u8> {
+        let mut hashes: Vec<[u8; 20]> = passwords.iter().map(|p| password_hash(p)).collect();
+        hashes.sort();
+        let mut text = String::new();
+        for (i, hash) in hashes.iter().enumerate() {
+            text.push_str(&format!("{}:{}\r\n", hex(hash), i + 1));
+        }
+        text.into_bytes()
+    }
+
+    #[test]
+    fn test_password_hash() {
+        assert_eq!(
+            hex(&password_hash("password")),
+            "5BAA61E4C9B93F3F0682250B6CF8331B7EE68FD8"
+        );
+    }
+
+    #[test]
+    fn test_text_and_index_lookups_agree() {
+        let breached = [
+            "password", "123456", "qwerty", "letmein", "dragon", "monkey",
+        ];
+        let text = dump(&breached);
+        let mut index = Vec::new();
+        assert_eq!(build_index(Cursor::new(&text), &mut index).unwrap(), 6);
+        assert_eq!(index.len(), 8 + 6 * 24);
+
+        let mut text_db = BreachDb::from_reader(Cursor::new(text)).unwrap();
+        let mut index_db = BreachDb::from_reader(Cursor::new(index)).unwrap();
+        assert_eq!(text_db.format(), BreachDbFormat::Text);
+        assert_eq!(index_db.format(), BreachDbFormat::Index);
+        for password in breached {
+            let count = text_db.lookup(password).unwrap();
+            assert!(count.is_some(), "{}", password);
+            assert_eq!(index_db.lookup(password).unwrap(), count);
+        }
+        for password in ["correct horse", "", "Password"] {
+            assert_eq!(text_db.lookup(password).unwrap(), None);
+            assert_eq!(index_db.lookup(password).unwrap(), None);
+        }
+    }
+
+    #[test]
+    fn test_take_unbreached_is_bounded() {
+        let digits: Vec<String> = (0..10).map(|d| d.to_string()).collect();
+        let all: Vec<&str> = digits.iter().map(String::as_str).collect();
+        let pins = |seed: &str| {
+            let rng = seeded_rng(seed).unwrap();
+            let mut generator = GeneratorConfig::new(Style::Pin)
+                .length(1)
+                .build_with_rng(rng)
+                .unwrap();
+            std::iter::from_fn(move || Some(generator.generate_one()))
+        };
+
+        let mut db = BreachDb::from_reader(Cursor::new(dump(&all[..9]))).unwrap();
+        let (taken, skipped) = db.take_unbreached(pins("01"), 5).unwrap();
+        assert_eq!(taken, ["9"; 5]);
+        assert!(skipped > 0);
+
+        let mut db = BreachDb::from_reader(Cursor::new(dump(&all))).unwrap();
+        assert!(matches!(
+            db.take_unbreached(pins("02"), 1),
+            Err(GenixError::TooManyBreached(MAX_BREACHED_IN_A_ROW))
+        ));
+    }
+
+    #[test]
+    fn test_build_index_rejects_bad_dumps() {
+        let unsorted = dump(&["password", "123456"])
+            .split(|&b| b == b'\n')
+            .rev()
+            .collect::<Vec<_>>()
+            .join(&b'\n');
+        for bad in [unsorted, b"not a hash:1\n".to_vec()] {
+            assert!(matches!(
+                build_index(Cursor::new(bad), Vec::new()),
+                Err(GenixError::InvalidBreachDb(_))
+            ));
+        }
+        let mut truncated = INDEX_MAGIC.to_vec();
+        truncated.extend_from_slice(&[0; 10]);
+        assert!(BreachDb::from_reader(Cursor::new(truncated)).is_err());
+    }
+}
@@ -0,0 +1,176 @@
+//! Character pool composition for the `random` style.
+//!
+//! A `CharsetSpec` describes which characters `random` may draw from: a set of
+//! classes (lowercase, uppercase, digits, symbols) or an explicit `charset`,
+//! minus any excluded characters. `pool()` resolves the spec into the exact,
+//! de-duplicated list of characters; both the generator and the entropy hint
+//! use it so `--min-entropy` always reflects the real pool.
+
+use std::fmt;
+
+pub const UPPER: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+pub const LOWER: &str = "abcdefghijklmnopqrstuvwxyz";
+pub const DIGITS: &str = "0123456789";
+/// Default symbol class used when no `symbols_set` is given.
+pub const SYMBOLS: &str = "!@#$%&*()-_=+[]{};:,.<>?/`~";
+/// Characters removed by `no_ambiguous`.
+pub const AMBIGUOUS: &str = "1lI0O|";
+
+/// Character class of a single character, as used by policies and the
+/// entropy estimator: anything that is not an ASCII letter or digit counts as a
+/// symbol.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CharClass {
+    Upper,
+    Lower,
+    Digit,
+    Symbol,
+}
+
+impl CharClass {
+    /// Every class, in pool composition order.
+    pub const ALL: [CharClass; 4] = [
+        CharClass::Upper,
+        CharClass::Lower,
+        CharClass::Digit,
+        CharClass::Symbol,
+    ];
+
+    /// Classify `c`.
+    pub fn of(c: char) -> CharClass {
+        if c.is_ascii_uppercase() {
+            CharClass::Upper
+        } else if c.is_ascii_lowercase() {
+            CharClass::Lower
+        } else if c.is_ascii_digit() {
+            CharClass::Digit
+        } else {
+            CharClass::Symbol
+        }
+    }
+}
+
+impl fmt::Display for CharClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            CharClass::Upper => "uppercase",
+            CharClass::Lower => "lowercase",
+            CharClass::Digit => "digit",
+            CharClass::Symbol => "symbol",
+        })
+    }
+}
+
+/// Description of the character pool used by the `random` style.
+///
+/// The default spec enables every class and reproduces the historical
+/// 89-character printable pool.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CharsetSpec {
+    /// Include `a-z`.
+    pub lower: bool,
+    /// Include `A-Z`.
+    pub upper: bool,
+    /// Include `0-9`.
+    pub digits: bool,
+    /// Include the symbol class (`symbols_set` or `SYMBOLS`).
+    pub symbols: bool,
+    /// Replacement for the default symbol class.
+    pub symbols_set: Option<String>,
+    /// Explicit pool; when set, the class flags and `symbols_set` are ignored.
+    pub charset: Option<String>,
+    /// Characters removed from the pool after composition.
+    pub exclude: String,
+    /// Also remove `AMBIGUOUS` characters.
+    pub no_ambiguous: bool,
+}
+
+impl Default for CharsetSpec {
+    fn default() -> Self {
+        CharsetSpec {
+            lower: true,
+            upper: true,
+            digits: true,
+            symbols: true,
+            symbols_set: None,
+            charset: None,
+            exclude: String::new(),
+            no_ambiguous: false,
+        }
+    }
+}
+
+impl CharsetSpec {
+    /// Resolve the spec into the characters that will actually be drawn.
+    ///
+    /// Classes are added in the order upper, lower, digits, symbols; duplicates
+    /// are dropped (keeping the first occurrence) so every character in the
+    /// pool is equally likely and the pool length is the true charset size.
+    pub fn pool(&self) -> Vec<char> {
+        let mut composed = String::new();
+        match &self.charset {
+            Some(chars) => composed.push_str(chars),
+            None => {
+                if self.upper {
+                    composed.push_str(UPPER);
+                }
+                if self.lower {
+                    composed.push_str(LOWER);
+                }
+                if self.digits {
+                    composed.push_str(DIGITS);
+                }
+                if self.symbols {
+                    composed.push_str(self.symbols_set.as_deref().unwrap_or(SYMBOLS));
+                }
+            }
+        }
+
+        let mut pool: Vec<char> = Vec::new();
+        for c in composed.chars() {
+            if self.exclude.contains(c) || (self.no_ambiguous && AMBIGUOUS.contains(c)) {
+                continue;
+            }
+            if !pool.contains(&c) {
+                pool.push(c);
+            }
+        }
+        pool
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_pool_matches_printable() {
+        let pool: String = CharsetSpec::default().pool().into_iter().collect();
+        assert_eq!(pool, format!("{}{}{}{}", UPPER, LOWER, DIGITS, SYMBOLS));
+        assert_eq!(pool.len(), 89);
+    }
+
+    #[test]
+    fn test_classes_exclude_and_symbols_set() {
+        let spec = CharsetSpec {
+            upper: false,
+            lower: false,
+            symbols_set: Some("!!-_\"".into()),
+            exclude: "\"9".into(),
+            no_ambiguous: true,
+            ..CharsetSpec::default()
+        };
+        let pool: String = spec.pool().into_iter().collect();
+        assert_eq!(pool, "2345678!-_");
+    }
+
+    #[test]
+    fn test_explicit_charset_overrides_classes() {
+        let spec = CharsetSpec {
+            charset: Some("abcabc".into()),
+            lower: false,
+            ..CharsetSpec::default()
+        };
+        assert_eq!(spec.pool(), vec!['a', 'b', 'c']);
+    }
+}
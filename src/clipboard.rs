@@ -1,14 +1,16 @@
+use crate::error::GenixError;
+
 /// Copy `s` to the system clipboard.
 ///
 /// This is a thin wrapper around the `arboard` crate. On some platforms or in
 /// headless CI environments clipboard initialization may fail — callers should
 /// treat errors as non-fatal (the CLI prints a warning on failure).
 ///
-/// Returns `Ok(())` on success or `Err(String)` describing the failure.
-pub fn copy_to_clipboard(s: &str) -> Result<(), String> {
-    let mut ctx = arboard::Clipboard::new().map_err(|e| format!("clipboard init: {}", e))?;
-    ctx.set_text(s.to_owned())
-        .map_err(|e| format!("clipboard set: {}", e))
+/// Returns `Ok(())` on success, or `GenixError::ClipboardInit` /
+/// `GenixError::ClipboardSet` describing which step failed.
+pub fn copy_to_clipboard(s: &str) -> Result<(), GenixError> {
+    let mut ctx = arboard::Clipboard::new().map_err(GenixError::ClipboardInit)?;
+    ctx.set_text(s.to_owned()).map_err(GenixError::ClipboardSet)
 }
 
 #[cfg(test)]
@@ -0,0 +1,183 @@
+//! Physical-dice (Diceware) passphrases.
+//!
+//! A `DicewareList` maps groups of die rolls (`11111` .. `66666` for the EFF
+//! large list) to words. Nothing here touches an RNG: every bit of entropy
+//! comes from the dice, so the result is suitable for air-gapped credential
+//! ceremonies. Each word carries exactly `dice * log2(6)` bits.
+
+use std::collections::HashMap;
+
+use crate::error::GenixError;
+use crate::wordlist::{WordlistSource, load_numbered_wordlist};
+
+/// A complete numbered wordlist: one word for every possible roll group.
+#[derive(Debug, Clone)]
+pub struct DicewareList {
+    dice: usize,
+    words: HashMap<String, String>,
+}
+
+impl DicewareList {
+    /// Load and validate a numbered list from `source` (default `eff-large`).
+    ///
+    /// # Errors
+    /// Returns `GenixError::InvalidDiceware` unless every number has the same
+    /// number of dice, uses only the digits 1-6, appears once, and all `6^dice`
+    /// roll groups are covered; wordlist loading errors are passed through.
+    pub fn load(source: Option<&WordlistSource>) -> Result<DicewareList, GenixError> {
+        DicewareList::from_entries(load_numbered_wordlist(source)?)
+    }
+
+    /// Build a list from `(dice number, word)` pairs; see `load`.
+    pub fn from_entries(entries: Vec<(String, String)>) -> Result<DicewareList, GenixError> {
+        let dice = entries.first().map_or(0, |(number, _)| number.len());
+        if dice == 0 {
+            return Err(GenixError::InvalidDiceware("wordlist is empty".into()));
+        }
+        let mut words = HashMap::with_capacity(entries.len());
+        for (number, word) in entries {
+            if number.len() != dice || !is_roll(&number) {
+                return Err(GenixError::InvalidDiceware(format!(
+                    "bad dice number {} (expected {} digits from 1 to 6)",
+                    number, dice
+                )));
+            }
+            if words.insert(number.clone(), word).is_some() {
+                return Err(GenixError::InvalidDiceware(format!(
+                    "dice number {} appears twice",
+                    number
+                )));
+            }
+        }
+        let expected = u32::try_from(dice)
+            .ok()
+            .and_then(|dice| 6usize.checked_pow(dice))
+            .ok_or_else(|| {
+                GenixError::InvalidDiceware(format!("{} dice per word is too many", dice))
+            })?;
+        if words.len() != expected {
+            return Err(GenixError::InvalidDiceware(format!(
+                "{} entries for {} dice, expected {}",
+                words.len(),
+                dice,
+                expected
+            )));
+        }
+        Ok(DicewareList { dice, words })
+    }
+
+    /// Number of dice rolled per word.
+    pub fn dice(&self) -> usize {
+        self.dice
+    }
+
+    /// Exact entropy of one word: `dice * log2(6)`.
+    pub fn bits_per_word(&self) -> f64 {
+        self.dice as f64 * 6f64.log2()
+    }
+
+    /// The word for one roll group such as `"41526"`. Whitespace inside the
+    /// group is ignored, so `"4 1 5 2 6"` works too.
+    ///
+    /// # Errors
+    /// Returns `GenixError::InvalidDiceware` if the group is not `dice`
+    /// digits from 1 to 6.
+    pub fn word(&self, roll: &str) -> Result<&str, GenixError> {
+        let roll: String = roll.chars().filter(|c| !c.is_whitespace()).collect();
+        if roll.len() != self.dice || !is_roll(&roll) {
+            return Err(GenixError::InvalidDiceware(format!(
+                "roll {:?} is not {} dice (digits 1-6)",
+                roll, self.dice
+            )));
+        }
+        Ok(self.words[&roll].as_str())
+    }
+
+    /// Split free-form input such as `"41526 23456"` or `"4 1 5 2 6"` into
+    /// roll groups of `dice` digits, ignoring whitespace.
+    ///
+    /// # Errors
+    /// Returns `GenixError::InvalidDiceware` for characters other than 1-6 or
+    /// a number of rolls that is not a multiple of `dice`.
+    pub fn split_rolls(&self, input: &str) -> Result<Vec<String>, GenixError> {
+        let faces: Vec<char> = input.chars().filter(|c| !c.is_whitespace()).collect();
+        if !faces.iter().all(|c| ('1'..='6').contains(c)) {
+            return Err(GenixError::InvalidDiceware(format!(
+                "{:?} contains something other than die faces 1-6",
+                input.trim()
+            )));
+        }
+        if !faces.len().is_multiple_of(self.dice) {
+            return Err(GenixError::InvalidDiceware(format!(
+                "{} rolls is not a whole number of {}-dice groups",
+                faces.len(),
+                self.dice
+            )));
+        }
+        Ok(faces
+            .chunks(self.dice)
+            .map(|group| group.iter().collect())
+            .collect())
+    }
+
+    /// Join the words for `rolls` with `separator`.
+    pub fn passphrase<S: AsRef<str>>(
+        &self,
+        rolls: &[S],
+        separator: &str,
+    ) -> Result<String, GenixError> {
+        let words = rolls
+            .iter()
+            .map(|roll| self.word(roll.as_ref()))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(words.join(separator))
+    }
+}
+
+/// True if every character of `s` is a die face.
+fn is_roll(s: &str) -> bool {
+    s.chars().all(|c| ('1'..='6').contains(&c))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn two_dice() -> DicewareList {
+        let entries = (0..36)
+            .map(|i| (format!("{}{}", i / 6 + 1, i % 6 + 1), format!("w{}", i)))
+            .collect();
+        DicewareList::from_entries(entries).unwrap()
+    }
+
+    #[test]
+    fn test_rolls_map_to_words() {
+        let list = two_dice();
+        assert_eq!(list.dice(), 2);
+        assert_eq!(list.word("11").unwrap(), "w0");
+        assert_eq!(list.word("6 6").unwrap(), "w35");
+        assert_eq!(list.passphrase(&["12", "21"], " ").unwrap(), "w1 w6");
+        assert!((list.bits_per_word() - 2.0 * 6f64.log2()).abs() < 1e-9);
+        assert_eq!(list.split_rolls(" 1 2\n6 6 ").unwrap(), vec!["12", "66"]);
+        assert!(list.split_rolls("123").is_err());
+    }
+
+    #[test]
+    fn test_bad_rolls_and_lists() {
+        let list = two_dice();
+        for roll in ["17", "0", "123", "ab"] {
+            assert!(matches!(
+                list.word(roll),
+                Err(GenixError::InvalidDiceware(_))
+            ));
+        }
+        let incomplete = vec![("11".to_string(), "a".to_string())];
+        assert!(DicewareList::from_entries(incomplete).is_err());
+        // 6^27 overflows usize
+        let too_long = vec![("1".repeat(27), "a".to_string())];
+        assert!(matches!(
+            DicewareList::from_entries(too_long),
+            Err(GenixError::InvalidDiceware(_))
+        ));
+    }
+}
@@ -1,27 +1,251 @@
 /// Entropy-related helpers (charset sizing and simple estimators).
+use std::collections::HashSet;
 use std::f64;
+use std::fmt;
+use std::str::FromStr;
 
-const DEFAULT_PRINTABLE: &str =
-    "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789!@#$%&*()-_=+[]{};:,.<>?/`~";
+use serde::Serialize;
+
+use crate::bip39;
+use crate::charset::CharsetSpec;
+use crate::error::GenixError;
+use crate::generate::Style;
+use crate::identifier::{ULID_RANDOM_BITS, UUID4_RANDOM_BITS, UUID7_RANDOM_BITS, nanoid_alphabet};
+use crate::matching::{GuessEstimate, estimate_guesses};
+use crate::passphrase::ParsedPassphrase;
+use crate::pattern::{Pattern, Token};
+use crate::policy::Policy;
+use crate::token;
+use crate::wordlist::{WordlistSource, load_wordlist};
 
 /// Return a conservative charset size hint for a named style.
-pub fn charset_size_for_style(style: &str, no_ambiguous: bool) -> Option<usize> {
+///
+/// For `Style::Random` this is the size of the pool composed by `charset`, so
+/// custom classes, exclusions and `no_ambiguous` are all reflected; for
+/// `Style::Nanoid` it is the alphabet size. Identifiers whose characters are
+/// not all random (`uuid4`, `uuid7`, `ulid`, `token`) return `None`: use
+/// `style_entropy`, which reports their random bits.
+pub fn charset_size_for_style(style: Style, charset: &CharsetSpec) -> Option<usize> {
+    match style {
+        Style::Random => Some(charset.pool().len()),
+        Style::Pin => Some(10),
+        Style::Hex => Some(16),
+        Style::Base64 => Some(64),
+        Style::Base32 => Some(32),
+        Style::Nanoid => Some(nanoid_alphabet(charset).len()),
+        // fixed formats whose characters are not all random; see `style_entropy`
+        Style::Passphrase
+        | Style::Pattern
+        | Style::Pronounceable
+        | Style::Bip39
+        | Style::Uuid4
+        | Style::Uuid7
+        | Style::Ulid
+        | Style::Token => None,
+    }
+}
+
+/// Exact entropy (bits) of one item of `style` generated with `length`.
+///
+/// This supersedes `charset_size_for_style` for sizing output: it also covers
+/// styles whose characters carry unequal entropy (`pronounceable`) and counts
+/// `hex`/`base64`/`base32` by their random bytes rather than their encoded characters.
+/// Returns `None` when the entropy does not depend on `length` alone
+/// (`passphrase` needs the wordlist, `pattern` the template).
+pub fn style_entropy(style: Style, length: usize, charset: &CharsetSpec) -> Option<f64> {
+    let length_f = length as f64;
     match style {
-        "random" => {
-            let mut set = DEFAULT_PRINTABLE.chars().count();
-            if no_ambiguous {
-                set = set.saturating_sub(6); // remove 1,l,I,0,O,|
-            }
-            Some(set)
+        Style::Random => Some(length_f * (charset.pool().len() as f64).log2()),
+        Style::Pin => Some(length_f * 10f64.log2()),
+        Style::Hex | Style::Base64 | Style::Base32 => Some(length_f * 8.0),
+        Style::Pronounceable => Some(pattern_entropy(&Pattern::pronounceable(length), 0)),
+        // the checksum word bits are derived, not random
+        Style::Bip39 => Some(length_f * 32.0 / 3.0),
+        Style::Nanoid => Some(length_f * (nanoid_alphabet(charset).len() as f64).log2()),
+        Style::Uuid4 => Some(UUID4_RANDOM_BITS),
+        Style::Uuid7 => Some(UUID7_RANDOM_BITS),
+        Style::Ulid => Some(ULID_RANDOM_BITS),
+        Style::Token => Some(length_f * 62f64.log2()),
+        Style::Passphrase | Style::Pattern => None,
+    }
+}
+
+/// Exact entropy (bits) of a `length`-character password drawn uniformly from
+/// the strings over `pool` that satisfy `policy`.
+///
+/// This is `length * log2(pool)` reduced by the share of strings the policy
+/// rules out; with an empty policy the two are equal.
+pub fn policy_entropy(pool: &[char], length: usize, policy: &Policy) -> f64 {
+    let unconstrained = length as f64 * (pool.len() as f64).log2();
+    unconstrained + policy.compliant_fraction(pool, length).log2()
+}
+
+/// Exact entropy (bits) of strings generated from `pattern`.
+///
+/// Each class placeholder contributes `log2(class size)`, each word
+/// placeholder `log2(wordlist_size)`, and literals contribute nothing.
+pub fn pattern_entropy(pattern: &Pattern, wordlist_size: usize) -> f64 {
+    pattern
+        .tokens()
+        .iter()
+        .map(|token| match token {
+            Token::Class(set) => (set.len() as f64).log2(),
+            Token::Word { .. } => (wordlist_size as f64).log2(),
+            Token::Literal(_) => 0.0,
+        })
+        .sum()
+}
+
+/// Strength model used by the string estimators.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Estimator {
+    /// `length * log2(charset)` from the character classes present, or the
+    /// exact entropy of the style hint.
+    #[default]
+    Naive,
+    /// The naive estimate, lowered to `log2` of the guesses a pattern-aware
+    /// attacker needs (see `matching::estimate_guesses`) when that is smaller.
+    Guesses,
+}
+
+impl Estimator {
+    /// Every estimator, in the order shown in help text.
+    pub const ALL: [Estimator; 2] = [Estimator::Naive, Estimator::Guesses];
+
+    /// The command-line name of the estimator.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Estimator::Naive => "naive",
+            Estimator::Guesses => "guesses",
         }
-        "pin" => Some(10),
-        "hex" => Some(16),
-        "base64" => Some(64),
-        "passphrase" => None,
-        _ => None,
     }
 }
 
+impl fmt::Display for Estimator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Estimator {
+    type Err = GenixError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Estimator::ALL
+            .into_iter()
+            .find(|estimator| estimator.as_str() == s)
+            .ok_or_else(|| GenixError::UnknownEstimator(s.to_string()))
+    }
+}
+
+/// Options for the string estimators that cannot be inferred from the input.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EstimateOptions {
+    /// Separator between passphrase words. When `None`, the last non-letter,
+    /// non-digit character between two words is taken as the separator.
+    pub separator: Option<String>,
+    /// Wordlist the passphrase was drawn from; `None` means `eff-large`, the
+    /// generator's default. Each word is looked up (case-insensitively) and
+    /// credited `log2(unique words)` bits; words not in the list fall back to
+    /// character-based entropy.
+    pub wordlist: Option<WordlistSource>,
+    /// Strength model; the library default is `Estimator::Naive`, the CLI
+    /// uses `Estimator::Guesses`.
+    pub estimator: Estimator,
+    /// Attacker guess rates behind `EntropyProfile::crack_times`.
+    pub rates: AttackRates,
+}
+
+/// Guesses per second of the attacker models used for crack times.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct AttackRates {
+    /// Online attack against a service that rate-limits (100 per hour).
+    pub online_throttled: f64,
+    /// Online attack against a service without rate limiting.
+    pub online_unthrottled: f64,
+    /// Offline attack on a slow, salted hash such as bcrypt or argon2.
+    pub offline_slow: f64,
+    /// Offline attack on a fast hash such as MD5 or NTLM with a GPU rig.
+    pub offline_fast: f64,
+}
+
+impl AttackRates {
+    /// The zxcvbn attacker models.
+    pub const DEFAULT: AttackRates = AttackRates {
+        online_throttled: 100.0 / 3600.0,
+        online_unthrottled: 10.0,
+        offline_slow: 1e4,
+        offline_fast: 1e10,
+    };
+}
+
+impl Default for AttackRates {
+    fn default() -> Self {
+        AttackRates::DEFAULT
+    }
+}
+
+/// Seconds each attacker model needs to try `2^bits` guesses.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct CrackTimes {
+    pub online_throttled: f64,
+    pub online_unthrottled: f64,
+    pub offline_slow: f64,
+    pub offline_fast: f64,
+}
+
+impl CrackTimes {
+    /// Crack times for `bits` of entropy (or `log2` guesses) at `rates`.
+    pub fn new(bits: f64, rates: &AttackRates) -> Self {
+        let guesses = bits.exp2();
+        CrackTimes {
+            online_throttled: guesses / rates.online_throttled,
+            online_unthrottled: guesses / rates.online_unthrottled,
+            offline_slow: guesses / rates.offline_slow,
+            offline_fast: guesses / rates.offline_fast,
+        }
+    }
+
+    /// `(scenario, seconds)` pairs, slowest attacker first.
+    pub fn scenarios(&self) -> [(&'static str, f64); 4] {
+        [
+            ("online, throttled", self.online_throttled),
+            ("online, unthrottled", self.online_unthrottled),
+            ("offline, slow hash", self.offline_slow),
+            ("offline, fast hash", self.offline_fast),
+        ]
+    }
+}
+
+/// Render `seconds` as a rounded duration such as `3 hours` or `centuries`.
+pub fn display_duration(seconds: f64) -> String {
+    const MINUTE: f64 = 60.0;
+    const HOUR: f64 = 60.0 * MINUTE;
+    const DAY: f64 = 24.0 * HOUR;
+    const MONTH: f64 = 31.0 * DAY;
+    const YEAR: f64 = 12.0 * MONTH;
+    const CENTURY: f64 = 100.0 * YEAR;
+    if seconds < 1.0 {
+        return "less than a second".to_string();
+    }
+    if seconds >= CENTURY {
+        return "centuries".to_string();
+    }
+    let (amount, unit) = [
+        (YEAR, "year"),
+        (MONTH, "month"),
+        (DAY, "day"),
+        (HOUR, "hour"),
+        (MINUTE, "minute"),
+    ]
+    .into_iter()
+    .find(|(size, _)| seconds >= *size)
+    .map_or((seconds, "second"), |(size, unit)| (seconds / size, unit));
+    let amount = amount.round() as u64;
+    let plural = if amount == 1 { "" } else { "s" };
+    format!("{} {}{}", amount, unit, plural)
+}
+
 /// Estimate the entropy (in bits) of a provided string using a lightweight
 /// heuristic.
 ///
@@ -29,74 +253,44 @@ pub fn charset_size_for_style(style: &str, no_ambiguous: bool) -> Option<usize>
 /// useful for CLI feedback and tests, not a replacement for full-strength
 /// password analysis libraries. The rules used are:
 ///
-/// - For `style == "passphrase"`, split on `-` and assume a default wordlist
-///   size (2048) when computing bits per word: bits = words * log2(wordlist_size).
+/// - For `Style::Passphrase`, split the input into words (see
+///   `passphrase::ParsedPassphrase`) and compute bits = words * log2(wordlist_size),
+///   plus the entropy of any random capitalization, separators, digits and
+///   symbols. The wordlist is `EstimateOptions::wordlist`, or `eff-large` as
+///   for generation; words missing from it are scored per character.
+/// - For `Style::Token`, verify the CRC32 checksum and report
+///   `random chars * log2(62)`.
+/// - For `Style::Bip39`, validate the mnemonic (word count, membership,
+///   checksum) and report the exact 128-256 random bits it encodes.
 /// - Otherwise, detect character classes used in the string (lowercase,
 ///   uppercase, digits, symbols) and compute bits = length * log2(charset_size),
 ///   where charset_size is the sum of the detected classes.
 /// - If detection yields an implausibly small charset, the function falls back
 ///   to the named style hint (via `charset_size_for_style`) when available.
 ///
+/// With `Estimator::Guesses` the result is additionally capped at `log2` of
+/// the pattern-aware guess estimate, so `Password1!` is no longer "strong".
+///
 /// # Errors
-/// Returns `Err(String)` when a charset cannot be determined (for example, an
+/// Returns `Err(GenixError::UnknownCharset)` when a charset cannot be determined (for example, an
 /// empty input and no relevant style hint).
-pub fn estimate_entropy_for_str(s: &str, style: &str) -> Result<f64, String> {
-    if style == "passphrase" {
-        let words: Vec<&str> = s.split('-').filter(|w| !w.is_empty()).collect();
-        let wordlist_size = 2048.0f64; // reasonable default for Diceware/EFF-style lists
-        return Ok((words.len() as f64) * wordlist_size.log2());
-    }
-
-    // Auto-detect character classes
-    let mut has_lower = false;
-    let mut has_upper = false;
-    let mut has_digit = false;
-    let mut has_symbol = false;
-    for ch in s.chars() {
-        if ch.is_ascii_lowercase() {
-            has_lower = true;
-        } else if ch.is_ascii_uppercase() {
-            has_upper = true;
-        } else if ch.is_ascii_digit() {
-            has_digit = true;
-        } else {
-            // treat everything else as a symbol (space, punctuation, unicode)
-            has_symbol = true;
-        }
-    }
-
-    let mut charset = 0usize;
-    if has_lower {
-        charset += 26;
-    }
-    if has_upper {
-        charset += 26;
-    }
-    if has_digit {
-        charset += 10;
-    }
-    if has_symbol {
-        // approximate number of printable symbols commonly available
-        charset += 32;
-    }
-
-    // If detection failed (e.g., empty string), try style hint
-    if charset < 2
-        && let Some(hint) = charset_size_for_style(style, false)
-    {
-        charset = hint;
-    }
-
-    if charset < 2 {
-        return Err("cannot determine charset size for entropy estimation".into());
-    }
+pub fn estimate_entropy_for_str(s: &str, style: Style) -> Result<f64, GenixError> {
+    estimate_entropy_for_str_with(s, style, &EstimateOptions::default())
+}
 
-    let per_char = (charset as f64).log2();
-    Ok(per_char * (s.chars().count() as f64))
+/// Like `estimate_entropy_for_str`, with explicit `EstimateOptions`.
+pub fn estimate_entropy_for_str_with(
+    s: &str,
+    style: Style,
+    options: &EstimateOptions,
+) -> Result<f64, GenixError> {
+    estimate_entropy_detailed_with(s, style, options).map(|profile| profile.bits)
 }
 
 /// Detailed entropy profile structure returned by `estimate_entropy_detailed`.
-#[derive(Debug)]
+///
+/// Serializes (for `--format json`) with the field names below.
+#[derive(Debug, Serialize)]
 pub struct EntropyProfile {
     /// Estimated total entropy in bits
     pub bits: f64,
@@ -111,69 +305,145 @@ pub struct EntropyProfile {
     pub has_upper: bool,
     pub has_digit: bool,
     pub has_symbol: bool,
-    /// For passphrase: word count and assumed wordlist size
+    /// For passphrase: word count and the size of the wordlist the words were
+    /// looked up in (2048 for BIP39)
     pub word_count: Option<usize>,
     pub assumed_wordlist_size: Option<usize>,
+    /// For passphrase: words not found in the wordlist, which were
+    /// scored with the character-based estimate instead
+    pub unlisted_words: Vec<String>,
+    /// For passphrase: bits credited to capitalization, separators and
+    /// appended digits/symbols (already included in `bits`)
+    pub format_bits: Option<f64>,
+    /// With `Estimator::Guesses`: the guess estimate and its match sequence
+    pub guesses: Option<GuessEstimate>,
+    /// Time to try `2^bits` guesses at `EstimateOptions::rates`
+    pub crack_times: CrackTimes,
 }
 
 /// Return a detailed entropy profile for `s` using heuristics tuned for the CLI.
-pub fn estimate_entropy_detailed(s: &str, style: &str) -> Result<EntropyProfile, String> {
-    if style == "passphrase" {
-        let words: Vec<&str> = s.split('-').filter(|w| !w.is_empty()).collect();
-        let wordlist_size = 2048usize;
-        let bits = (words.len() as f64) * (wordlist_size as f64).log2();
+pub fn estimate_entropy_detailed(s: &str, style: Style) -> Result<EntropyProfile, GenixError> {
+    estimate_entropy_detailed_with(s, style, &EstimateOptions::default())
+}
+
+/// Like `estimate_entropy_detailed`, with explicit `EstimateOptions`.
+pub fn estimate_entropy_detailed_with(
+    s: &str,
+    style: Style,
+    options: &EstimateOptions,
+) -> Result<EntropyProfile, GenixError> {
+    let mut profile = naive_profile(s, style, options)?;
+    if options.estimator == Estimator::Guesses {
+        let estimate = estimate_guesses(s);
+        profile.bits = profile.bits.min(estimate.guesses_log2);
+        profile.guesses = Some(estimate);
+    }
+    profile.crack_times = CrackTimes::new(profile.bits, &options.rates);
+    Ok(profile)
+}
+
+/// The style-aware `Estimator::Naive` profile.
+fn naive_profile(
+    s: &str,
+    style: Style,
+    options: &EstimateOptions,
+) -> Result<EntropyProfile, GenixError> {
+    if style == Style::Token {
+        let bits = token::validate_token(s)?;
+        let random_chars = (bits / 62f64.log2()).round() as usize;
+        return Ok(EntropyProfile {
+            bits,
+            charset_size: 62,
+            per_char: 62f64.log2(),
+            length: random_chars,
+            has_lower: false,
+            has_upper: false,
+            has_digit: false,
+            has_symbol: false,
+            word_count: None,
+            assumed_wordlist_size: None,
+            unlisted_words: Vec::new(),
+            format_bits: None,
+            guesses: None,
+            crack_times: CrackTimes::default(),
+        });
+    }
+    if style == Style::Bip39 {
+        let bits = bip39::validate_mnemonic(s)? as f64;
+        let words = s.split_whitespace().count();
+        return Ok(EntropyProfile {
+            bits,
+            charset_size: 2048,
+            per_char: bits / words as f64,
+            length: words,
+            has_lower: false,
+            has_upper: false,
+            has_digit: false,
+            has_symbol: false,
+            word_count: Some(words),
+            assumed_wordlist_size: Some(2048),
+            unlisted_words: Vec::new(),
+            format_bits: None,
+            guesses: None,
+            crack_times: CrackTimes::default(),
+        });
+    }
+    if style == Style::Passphrase {
+        let parsed = ParsedPassphrase::parse(s, options.separator.as_deref());
+        let words = parsed.words.len();
+        let format_bits = parsed.extra_entropy();
+        // the generator's default list when none is given
+        let list: HashSet<String> = load_wordlist(options.wordlist.as_ref())?
+            .into_iter()
+            .map(|w| w.to_lowercase())
+            .collect();
+        let unlisted_words: Vec<String> = parsed
+            .words
+            .iter()
+            .filter(|w| !list.contains(&w.to_lowercase()))
+            .cloned()
+            .collect();
+        let wordlist_size = list.len();
+        let unlisted_bits: f64 = unlisted_words
+            .iter()
+            .map(|w: &String| {
+                let size = class_charset_size(w).max(1) as f64;
+                size.log2() * w.chars().count() as f64
+            })
+            .sum();
+        let listed = words - unlisted_words.len();
+        let bits =
+            (listed as f64) * (wordlist_size.max(1) as f64).log2() + unlisted_bits + format_bits;
         return Ok(EntropyProfile {
             bits,
             charset_size: wordlist_size,
             per_char: (wordlist_size as f64).log2(),
-            length: words.len(),
+            length: words,
             has_lower: false,
             has_upper: false,
             has_digit: false,
             has_symbol: false,
-            word_count: Some(words.len()),
+            word_count: Some(words),
             assumed_wordlist_size: Some(wordlist_size),
+            unlisted_words,
+            format_bits: Some(format_bits),
+            guesses: None,
+            crack_times: CrackTimes::default(),
         });
     }
     // Use a conservative class-based estimator.
-    let mut has_lower = false;
-    let mut has_upper = false;
-    let mut has_digit = false;
-    let mut has_symbol = false;
-    for ch in s.chars() {
-        if ch.is_ascii_lowercase() {
-            has_lower = true;
-        } else if ch.is_ascii_uppercase() {
-            has_upper = true;
-        } else if ch.is_ascii_digit() {
-            has_digit = true;
-        } else {
-            has_symbol = true;
-        }
-    }
-
-    let mut charset = 0usize;
-    if has_lower {
-        charset += 26;
-    }
-    if has_upper {
-        charset += 26;
-    }
-    if has_digit {
-        charset += 10;
-    }
-    if has_symbol {
-        charset += 32;
-    }
+    let (has_lower, has_upper, has_digit, has_symbol) = detect_classes(s);
+    let mut charset = class_charset_size(s);
 
+    // If detection failed (e.g., empty string), try style hint
     if charset < 2
-        && let Some(hint) = charset_size_for_style(style, false)
+        && let Some(hint) = charset_size_for_style(style, &CharsetSpec::default())
     {
         charset = hint;
     }
 
     if charset < 2 {
-        return Err("cannot determine charset size for entropy estimation".into());
+        return Err(GenixError::UnknownCharset);
     }
 
     let per_char = (charset as f64).log2();
@@ -191,9 +461,51 @@ pub fn estimate_entropy_detailed(s: &str, style: &str) -> Result<EntropyProfile,
         has_symbol,
         word_count: None,
         assumed_wordlist_size: None,
+        unlisted_words: Vec::new(),
+        format_bits: None,
+        guesses: None,
+        crack_times: CrackTimes::default(),
     })
 }
 
+/// Which of the lowercase, uppercase, digit and symbol classes appear in `s`.
+fn detect_classes(s: &str) -> (bool, bool, bool, bool) {
+    let mut classes = (false, false, false, false);
+    for ch in s.chars() {
+        if ch.is_ascii_lowercase() {
+            classes.0 = true;
+        } else if ch.is_ascii_uppercase() {
+            classes.1 = true;
+        } else if ch.is_ascii_digit() {
+            classes.2 = true;
+        } else {
+            // treat everything else as a symbol (space, punctuation, unicode)
+            classes.3 = true;
+        }
+    }
+    classes
+}
+
+/// Sum of the sizes of the character classes present in `s`.
+pub(crate) fn class_charset_size(s: &str) -> usize {
+    let (has_lower, has_upper, has_digit, has_symbol) = detect_classes(s);
+    let mut charset = 0usize;
+    if has_lower {
+        charset += 26;
+    }
+    if has_upper {
+        charset += 26;
+    }
+    if has_digit {
+        charset += 10;
+    }
+    if has_symbol {
+        // approximate number of printable symbols commonly available
+        charset += 32;
+    }
+    charset
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -201,7 +513,7 @@ mod tests {
     #[test]
     fn test_entropy_lowercase_only() {
         let s = "lowercaseonly";
-        let bits = estimate_entropy_for_str(s, "random").unwrap();
+        let bits = estimate_entropy_for_str(s, Style::Random).unwrap();
         let per = (26f64).log2();
         assert!((bits - per * (s.len() as f64)).abs() < 1e-6);
     }
@@ -209,18 +521,161 @@ mod tests {
     #[test]
     fn test_entropy_mixed() {
         let s = "Ab3$";
-        let bits = estimate_entropy_for_str(s, "random").unwrap();
+        let bits = estimate_entropy_for_str(s, Style::Random).unwrap();
         // should detect lower+upper+digit+symbol => charset >= 26+26+10+32
         let charset = 26 + 26 + 10 + 32;
         let per = (charset as f64).log2();
         assert!((bits - per * (s.len() as f64)).abs() < 1e-6);
     }
 
+    #[test]
+    fn test_charset_hint_follows_spec() {
+        let default = CharsetSpec::default();
+        assert_eq!(charset_size_for_style(Style::Random, &default), Some(89));
+        let spec = CharsetSpec {
+            no_ambiguous: true,
+            symbols: false,
+            ..CharsetSpec::default()
+        };
+        assert_eq!(charset_size_for_style(Style::Random, &spec), Some(62 - 5));
+        assert_eq!(charset_size_for_style(Style::Pin, &spec), Some(10));
+    }
+
+    #[test]
+    fn test_policy_entropy_is_reduced() {
+        let pool = CharsetSpec::default().pool();
+        let free = policy_entropy(&pool, 10, &Policy::default());
+        assert!((free - 10.0 * 89f64.log2()).abs() < 1e-9);
+        let policy = Policy {
+            min_digit: 2,
+            min_symbol: 1,
+            ..Policy::default()
+        };
+        let constrained = policy_entropy(&pool, 10, &policy);
+        assert!(constrained < free && constrained > free - 3.0);
+    }
+
+    #[test]
+    fn test_pattern_entropy_is_exact() {
+        let pattern = Pattern::parse("Cvccvc-9999-ss").unwrap();
+        let expected = 3.0 * 21f64.log2()
+            + 21f64.log2()
+            + 2.0 * 5f64.log2()
+            + 4.0 * 10f64.log2()
+            + 2.0 * 27f64.log2();
+        assert!((pattern_entropy(&pattern, 0) - expected).abs() < 1e-9);
+        let words = Pattern::parse("w-w").unwrap();
+        assert!((pattern_entropy(&words, 7776) - 2.0 * 7776f64.log2()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_style_entropy() {
+        let spec = CharsetSpec::default();
+        let pron = style_entropy(Style::Pronounceable, 5, &spec).unwrap();
+        assert!((pron - (3.0 * 21f64.log2() + 2.0 * 5f64.log2())).abs() < 1e-9);
+        assert_eq!(style_entropy(Style::Hex, 16, &spec), Some(128.0));
+        assert_eq!(style_entropy(Style::Passphrase, 4, &spec), None);
+        assert_eq!(style_entropy(Style::Uuid4, 0, &spec), Some(122.0));
+        assert_eq!(style_entropy(Style::Uuid7, 0, &spec), Some(74.0));
+        assert_eq!(style_entropy(Style::Nanoid, 21, &spec), Some(126.0));
+    }
+
     #[test]
     fn test_entropy_passphrase() {
-        let s = "apple-banana-orange";
-        let bits = estimate_entropy_for_str(s, "passphrase").unwrap();
-        let expected = 3.0 * 2048f64.log2();
-        assert!((bits - expected).abs() < 1e-6);
+        // without a wordlist, words are looked up in the default eff-large
+        let profile = estimate_entropy_detailed_with(
+            "abacus-zoom-kiwiz",
+            Style::Passphrase,
+            &EstimateOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(profile.assumed_wordlist_size, Some(7776));
+        assert_eq!(profile.unlisted_words, vec!["kiwiz"]);
+        let expected = 2.0 * 7776f64.log2() + 5.0 * 26f64.log2();
+        assert!((profile.bits - expected).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_entropy_passphrase_with_format() {
+        let path = std::env::temp_dir().join(format!("genix-format-{}.txt", std::process::id()));
+        std::fs::write(&path, "apple\nbanana\norange\npear\n").unwrap();
+        let options = EstimateOptions {
+            separator: Some(" ".into()),
+            wordlist: Some(WordlistSource::File(path.display().to_string())),
+            ..EstimateOptions::default()
+        };
+        let profile =
+            estimate_entropy_detailed_with("Apple banana7 Orange", Style::Passphrase, &options)
+                .unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(profile.word_count, Some(3));
+        // random capitalization (3 bits) plus one digit on one of three words
+        let format = 3.0 + 10f64.log2() + 3f64.log2();
+        assert!((profile.format_bits.unwrap() - format).abs() < 1e-9);
+        assert!((profile.bits - (3.0 * 2.0 + format)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_entropy_passphrase_with_wordlist() {
+        let path = std::env::temp_dir().join(format!("genix-entropy-{}.txt", std::process::id()));
+        std::fs::write(&path, "apple\nbanana\norange\npear\n").unwrap();
+        let options = EstimateOptions {
+            wordlist: Some(WordlistSource::File(path.display().to_string())),
+            ..EstimateOptions::default()
+        };
+        let profile =
+            estimate_entropy_detailed_with("apple-Banana-kiwi", Style::Passphrase, &options)
+                .unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(profile.assumed_wordlist_size, Some(4));
+        assert_eq!(profile.unlisted_words, vec!["kiwi"]);
+        // two listed words at 2 bits each, "kiwi" as 4 lowercase letters
+        let expected = 2.0 * 2.0 + 4.0 * 26f64.log2() + profile.format_bits.unwrap();
+        assert!((profile.bits - expected).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_guesses_estimator_caps_naive_bits() {
+        let options = EstimateOptions {
+            estimator: Estimator::Guesses,
+            ..EstimateOptions::default()
+        };
+        let naive = estimate_entropy_for_str("Password1!", Style::Random).unwrap();
+        let profile =
+            estimate_entropy_detailed_with("Password1!", Style::Random, &options).unwrap();
+        assert!(naive > 64.0);
+        assert!(profile.bits < 40.0);
+        assert!(profile.guesses.is_some());
+        // the naive estimator stays the library default
+        let default =
+            estimate_entropy_detailed_with("Password1!", Style::Random, &Default::default())
+                .unwrap();
+        assert_eq!(default.bits, naive);
+        assert!(default.guesses.is_none());
+    }
+
+    #[test]
+    fn test_crack_times() {
+        let profile = estimate_entropy_detailed("aaaaaa", Style::Random).unwrap();
+        let times = profile.crack_times;
+        let guesses = profile.bits.exp2();
+        assert!((times.offline_slow - guesses / 1e4).abs() < 1e-9);
+        assert!(times.online_throttled > times.online_unthrottled);
+        assert!(times.offline_slow > times.offline_fast);
+        assert_eq!(display_duration(0.5), "less than a second");
+        assert_eq!(display_duration(1.0), "1 second");
+        assert_eq!(display_duration(7200.0), "2 hours");
+        assert_eq!(display_duration(f64::INFINITY), "centuries");
+    }
+
+    #[test]
+    fn test_entropy_bip39() {
+        let phrase = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+        assert_eq!(
+            estimate_entropy_for_str(phrase, Style::Bip39).unwrap(),
+            128.0
+        );
+        let bad = phrase.replace("about", "able");
+        assert!(estimate_entropy_for_str(&bad, Style::Bip39).is_err());
     }
 }
@@ -0,0 +1,185 @@
+//! Error type shared by the Genix library modules.
+//!
+//! Every public function in `generate`, `entropy` and `clipboard` returns
+//! `Result<_, GenixError>` so callers can match on the failure kind instead of
+//! inspecting error text. The `Display` output is what the CLI prints after
+//! `error:`.
+
+use std::fmt;
+use std::io;
+
+/// Structured error returned by the Genix library.
+#[derive(Debug)]
+pub enum GenixError {
+    /// The requested generation style is not recognised.
+    UnknownStyle(String),
+    /// The requested passphrase capitalization mode is not recognised.
+    UnknownCapitalize(String),
+    /// The requested base64 encoding is not recognised.
+    UnknownEncoding(String),
+    /// The requested length unit is not recognised.
+    UnknownLengthUnit(String),
+    /// The requested strength estimator is not recognised.
+    UnknownEstimator(String),
+    /// The requested output format is not recognised.
+    UnknownFormat(String),
+    /// A wordlist file could not be opened.
+    WordlistIo { path: String, source: io::Error },
+    /// A file or stream of candidates could not be read.
+    InputIo { path: String, source: io::Error },
+    /// The hidden password prompt needs a terminal and none is available.
+    NoTerminal(io::Error),
+    /// The confirmation typed at the prompt differs from the first entry.
+    ConfirmMismatch,
+    /// The name is not one of the built-in wordlists.
+    UnknownWordlist(String),
+    /// A built-in wordlist was compiled without its words.
+    WordlistUnavailable(String),
+    /// Strict mode refused a wordlist that fails the quality checks.
+    WordlistRejected(String),
+    /// A Diceware list or a die roll is malformed.
+    InvalidDiceware(String),
+    /// A BIP39 mnemonic must have 12, 15, 18, 21 or 24 words.
+    Bip39WordCount(usize),
+    /// A BIP39 mnemonic has unknown words or a bad checksum.
+    InvalidMnemonic(String),
+    /// A token prefix is malformed, or a token fails its checksum.
+    InvalidToken(String),
+    /// A TOTP secret, issuer, account or parameter is malformed.
+    InvalidTotp(String),
+    /// A breach database file could not be opened.
+    BreachDbIo { path: String, source: io::Error },
+    /// A breach database is malformed or could not be read or written.
+    InvalidBreachDb(String),
+    /// This many generated items in a row were found in the breach database.
+    TooManyBreached(usize),
+    /// The wordlist contained no usable words.
+    EmptyWordlist,
+    /// The composed character pool is empty after exclusions.
+    EmptyCharset,
+    /// A style's charset is too small to derive per-character entropy.
+    InvalidCharset,
+    /// No charset could be inferred from the input or the style hint.
+    UnknownCharset,
+    /// A policy file does not parse or declares an impossible rule.
+    InvalidPolicy(String),
+    /// A policy cannot be met with the configured style, pool or length.
+    PolicyUnsatisfiable(String),
+    /// A `pattern` template is missing or malformed.
+    InvalidPattern(String),
+    /// A fixed-shape style cannot reach the requested minimum entropy.
+    InsufficientEntropy { required: f64, available: f64 },
+    /// A character length cannot be produced by a padded encoding.
+    InvalidEncoding(String),
+    /// A selftest sample is too small for a stream's frequency test.
+    TooFewSamples {
+        stream: String,
+        observations: usize,
+        required: usize,
+    },
+    /// A `--seed` value is not usable as a ChaCha20 seed.
+    InvalidSeed(String),
+    /// The system clipboard could not be initialized.
+    ClipboardInit(arboard::Error),
+    /// The system clipboard refused the new contents.
+    ClipboardSet(arboard::Error),
+}
+
+impl fmt::Display for GenixError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GenixError::UnknownStyle(style) => write!(f, "unknown style: {}", style),
+            GenixError::UnknownCapitalize(mode) => {
+                write!(f, "unknown capitalization mode: {}", mode)
+            }
+            GenixError::UnknownEncoding(encoding) => write!(f, "unknown encoding: {}", encoding),
+            GenixError::UnknownLengthUnit(unit) => write!(f, "unknown length unit: {}", unit),
+            GenixError::UnknownEstimator(name) => write!(f, "unknown estimator: {}", name),
+            GenixError::UnknownFormat(format) => write!(f, "unknown output format: {}", format),
+            GenixError::WordlistIo { path, source } => {
+                write!(f, "failed to open wordlist {}: {}", path, source)
+            }
+            GenixError::InputIo { path, source } => {
+                write!(f, "failed to read {}: {}", path, source)
+            }
+            GenixError::NoTerminal(source) => write!(
+                f,
+                "cannot prompt for a password without a terminal ({}); pass it as an argument or use --stdin or --file",
+                source
+            ),
+            GenixError::ConfirmMismatch => write!(f, "the entries do not match"),
+            GenixError::UnknownWordlist(name) => write!(f, "unknown built-in wordlist: {}", name),
+            GenixError::WordlistUnavailable(name) => write!(
+                f,
+                "built-in wordlist {} is not available in this build (see assets/wordlists/)",
+                name
+            ),
+            GenixError::WordlistRejected(reasons) => write!(f, "wordlist rejected: {}", reasons),
+            GenixError::InvalidDiceware(reason) => write!(f, "invalid diceware input: {}", reason),
+            GenixError::Bip39WordCount(words) => write!(
+                f,
+                "BIP39 mnemonics have 12, 15, 18, 21 or 24 words, not {}",
+                words
+            ),
+            GenixError::InvalidMnemonic(reason) => write!(f, "invalid BIP39 mnemonic: {}", reason),
+            GenixError::InvalidToken(reason) => write!(f, "invalid token: {}", reason),
+            GenixError::InvalidTotp(reason) => write!(f, "invalid TOTP input: {}", reason),
+            GenixError::BreachDbIo { path, source } => {
+                write!(f, "failed to open breach database {}: {}", path, source)
+            }
+            GenixError::InvalidBreachDb(reason) => write!(f, "invalid breach database: {}", reason),
+            GenixError::TooManyBreached(count) => write!(
+                f,
+                "{} generated items in a row were found in the breach database; use a longer length or another style",
+                count
+            ),
+            GenixError::EmptyWordlist => write!(f, "wordlist is empty"),
+            GenixError::EmptyCharset => write!(f, "character pool is empty after exclusions"),
+            GenixError::InvalidCharset => {
+                write!(f, "invalid charset size for entropy calculation")
+            }
+            GenixError::UnknownCharset => {
+                write!(f, "cannot determine charset size for entropy estimation")
+            }
+            GenixError::InvalidPolicy(reason) => write!(f, "invalid policy: {}", reason),
+            GenixError::PolicyUnsatisfiable(reason) => {
+                write!(f, "policy cannot be satisfied: {}", reason)
+            }
+            GenixError::InvalidPattern(reason) => write!(f, "invalid pattern: {}", reason),
+            GenixError::InsufficientEntropy {
+                required,
+                available,
+            } => write!(
+                f,
+                "output has {:.2} bits of entropy, below the required {} bits",
+                available, required
+            ),
+            GenixError::InvalidEncoding(reason) => write!(f, "invalid encoding: {}", reason),
+            GenixError::TooFewSamples {
+                stream,
+                observations,
+                required,
+            } => write!(
+                f,
+                "selftest stream {} has {} observations but needs at least {}; raise --samples",
+                stream, observations, required
+            ),
+            GenixError::InvalidSeed(reason) => write!(f, "invalid seed: {}", reason),
+            GenixError::ClipboardInit(e) => write!(f, "clipboard init: {}", e),
+            GenixError::ClipboardSet(e) => write!(f, "clipboard set: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for GenixError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            GenixError::WordlistIo { source, .. }
+            | GenixError::InputIo { source, .. }
+            | GenixError::NoTerminal(source)
+            | GenixError::BreachDbIo { source, .. } => Some(source),
+            GenixError::ClipboardInit(e) | GenixError::ClipboardSet(e) => Some(e),
+            _ => None,
+        }
+    }
+}
@@ -1,109 +1,831 @@
 //! Generation helpers for Genix.
 //!
-//! This module exposes a single public function, `generate_many`, which
-//! supports several generation styles: `random`, `pin`, `hex`, `base64`, and
-//! `passphrase`. For `passphrase` a wordlist may be provided; otherwise a small
-//! built-in list is used for examples and tests.
+//! This module exposes the `Style` enum, the `GeneratorConfig` builder and the
+//! `Generator` it produces, plus the `generate_many` convenience wrapper. The
+//! supported styles are `random`, `pin`, `hex`, `base64`, `base32`,
+//! `passphrase`, `pattern`, `pronounceable`, `bip39`, the identifier styles
+//! `uuid4`, `uuid7`, `ulid` and `nanoid`, and checksummed API tokens (`token`).
+//! For `passphrase` (and word placeholders in patterns) words come from a
+//! `wordlist::WordlistSource`, by default the embedded EFF large list.
 //!
 //! The generator keeps a clear separation between entropy calculation and byte
 //! / character generation so other modules can test and reuse the logic.
 
-use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::fmt;
+use std::str::FromStr;
 
 use base64::{Engine as _, engine::general_purpose};
 use rand::distributions::Uniform;
-use rand::{Rng, thread_rng};
+use rand::rngs::ThreadRng;
+use rand::{CryptoRng, Rng, RngCore, SeedableRng, thread_rng};
+use rand_chacha::ChaCha20Rng;
 
-use crate::entropy::charset_size_for_style;
+use crate::bip39;
+use crate::charset::{CharsetSpec, DIGITS, SYMBOLS};
+use crate::entropy::{
+    EstimateOptions, charset_size_for_style, pattern_entropy, policy_entropy, style_entropy,
+};
+use crate::error::GenixError;
+use crate::identifier::{self, NANOID_LENGTH, nanoid_alphabet};
+use crate::passphrase::{Capitalize, PassphraseFormat, RANDOM_SEPARATORS};
+use crate::pattern::{Pattern, Token};
+use crate::policy::{Policy, PolicyChecker, PolicySampler};
+use crate::token;
+use crate::totp;
+use crate::wordlist::{WordlistSource, analyze, load_wordlist};
 
-const DEFAULT_PRINTABLE: &str =
-    "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789!@#$%&*()-_=+[]{};:,.<>?/`~";
-const AMBIGUOUS: &str = "1lI0O|";
+/// Generation style understood by `generate_many` and the entropy helpers.
+///
+/// Parses from (and displays as) the lowercase names used on the command line,
+/// so clap rejects unknown `--style` values before any work is done.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Style {
+    Random,
+    Passphrase,
+    Pin,
+    Hex,
+    Base64,
+    Base32,
+    Pattern,
+    Pronounceable,
+    Bip39,
+    Uuid4,
+    Uuid7,
+    Ulid,
+    Nanoid,
+    Token,
+}
+
+impl Style {
+    /// Every supported style, in the order shown in help text.
+    pub const ALL: [Style; 14] = [
+        Style::Random,
+        Style::Passphrase,
+        Style::Pin,
+        Style::Hex,
+        Style::Base64,
+        Style::Base32,
+        Style::Pattern,
+        Style::Pronounceable,
+        Style::Bip39,
+        Style::Uuid4,
+        Style::Uuid7,
+        Style::Ulid,
+        Style::Nanoid,
+        Style::Token,
+    ];
+
+    /// The command-line name of the style.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Style::Random => "random",
+            Style::Passphrase => "passphrase",
+            Style::Pin => "pin",
+            Style::Hex => "hex",
+            Style::Base64 => "base64",
+            Style::Base32 => "base32",
+            Style::Pattern => "pattern",
+            Style::Pronounceable => "pronounceable",
+            Style::Bip39 => "bip39",
+            Style::Uuid4 => "uuid4",
+            Style::Uuid7 => "uuid7",
+            Style::Ulid => "ulid",
+            Style::Nanoid => "nanoid",
+            Style::Token => "token",
+        }
+    }
+
+    /// Length used when none is given: 12 words for `bip39`, 21 characters
+    /// for `nanoid`, 30 random characters for `token`, and 20 otherwise.
+    /// Fixed-format identifiers ignore it.
+    pub fn default_length(self) -> usize {
+        match self {
+            Style::Bip39 => 12,
+            Style::Nanoid => NANOID_LENGTH,
+            Style::Token => 30,
+            _ => 20,
+        }
+    }
+}
+
+impl fmt::Display for Style {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Style {
+    type Err = GenixError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Style::ALL
+            .into_iter()
+            .find(|style| style.as_str() == s)
+            .ok_or_else(|| GenixError::UnknownStyle(s.to_string()))
+    }
+}
 
-/// Generate `count` items using `style` with optional `wordlist`.
+/// Alphabet and padding of the `base64` style.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Base64Encoding {
+    /// RFC 4648 `+/` alphabet with `=` padding.
+    #[default]
+    Standard,
+    /// RFC 4648 URL- and filename-safe `-_` alphabet with `=` padding.
+    Url,
+    /// URL-safe alphabet without padding (cookies, URLs, JWT segments).
+    UrlNoPad,
+    /// Standard alphabet without padding.
+    StandardNoPad,
+}
+
+impl Base64Encoding {
+    /// Every encoding, in the order shown in help text.
+    pub const ALL: [Base64Encoding; 4] = [
+        Base64Encoding::Standard,
+        Base64Encoding::Url,
+        Base64Encoding::UrlNoPad,
+        Base64Encoding::StandardNoPad,
+    ];
+
+    /// The command-line name of the encoding.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Base64Encoding::Standard => "standard",
+            Base64Encoding::Url => "url",
+            Base64Encoding::UrlNoPad => "url-nopad",
+            Base64Encoding::StandardNoPad => "standard-nopad",
+        }
+    }
+
+    /// True if the output is padded with `=` to a multiple of 4 characters.
+    pub fn padded(self) -> bool {
+        matches!(self, Base64Encoding::Standard | Base64Encoding::Url)
+    }
+
+    fn encode(self, bytes: &[u8]) -> String {
+        match self {
+            Base64Encoding::Standard => general_purpose::STANDARD.encode(bytes),
+            Base64Encoding::Url => general_purpose::URL_SAFE.encode(bytes),
+            Base64Encoding::UrlNoPad => general_purpose::URL_SAFE_NO_PAD.encode(bytes),
+            Base64Encoding::StandardNoPad => general_purpose::STANDARD_NO_PAD.encode(bytes),
+        }
+    }
+}
+
+impl fmt::Display for Base64Encoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Base64Encoding {
+    type Err = GenixError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Base64Encoding::ALL
+            .into_iter()
+            .find(|encoding| encoding.as_str() == s)
+            .ok_or_else(|| GenixError::UnknownEncoding(s.to_string()))
+    }
+}
+
+/// What `length` counts for the `hex`, `base64` and `base32` styles.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum LengthUnit {
+    /// Random bytes before encoding.
+    #[default]
+    Bytes,
+    /// Characters of encoded output, each fully random.
+    Chars,
+}
+
+impl LengthUnit {
+    /// Every unit, in the order shown in help text.
+    pub const ALL: [LengthUnit; 2] = [LengthUnit::Bytes, LengthUnit::Chars];
+
+    /// The command-line name of the unit.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LengthUnit::Bytes => "bytes",
+            LengthUnit::Chars => "chars",
+        }
+    }
+}
+
+impl fmt::Display for LengthUnit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for LengthUnit {
+    type Err = GenixError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        LengthUnit::ALL
+            .into_iter()
+            .find(|unit| unit.as_str() == s)
+            .ok_or_else(|| GenixError::UnknownLengthUnit(s.to_string()))
+    }
+}
+
+/// Options for a `Generator`, assembled with builder-style setters.
+///
+/// Start from `GeneratorConfig::new(style)` (or `Default`, which is the CLI's
+/// `random`/20 setup), chain the setters you need and call `build()`:
 ///
-/// Parameters
-/// - `style`: generation style (`random`, `pin`, `hex`, `base64`, `passphrase`).
-/// - `length`: length meaning depends on style (characters for `random`/`pin`,
-///   bytes for `hex`/`base64`, word count for `passphrase`).
-/// - `count`: how many items to produce.
-/// - `wordlist`: optional path to a newline-delimited wordlist file (for
-///   `passphrase`). If `None` a small builtin list is used.
-/// - `no_ambiguous`: if true, removes ambiguous characters for `random` style.
-/// - `min_entropy`: optional minimum entropy target (bits). If provided and the
-///   style supports a charset hint, the function may increase `length` to
-///   satisfy the requested entropy.
+/// ```
+/// use genix_lib::generate::{GeneratorConfig, Style};
 ///
-/// Returns
-/// - `Ok(Vec<String>)` on success with `count` generated items.
-/// - `Err(String)` on fatal errors (for example, unknown style or missing
-///   wordlist file).
-pub fn generate_many(
-    style: &str,
-    mut length: usize,
-    count: usize,
-    wordlist: Option<&str>,
-    no_ambiguous: bool,
+/// let mut generator = GeneratorConfig::new(Style::Pin).length(6).build().unwrap();
+/// assert_eq!(generator.generate_one().unwrap().len(), 6);
+/// ```
+#[derive(Debug, Clone)]
+pub struct GeneratorConfig {
+    style: Style,
+    length: usize,
+    charset: CharsetSpec,
+    policy: Policy,
+    pattern: Option<String>,
+    passphrase_format: PassphraseFormat,
+    token_prefix: String,
+    padding: bool,
+    encoding: Base64Encoding,
+    length_unit: LengthUnit,
+    wordlist: Option<WordlistSource>,
+    strict_wordlist: bool,
     min_entropy: Option<f64>,
-) -> Result<Vec<String>, String> {
-    if let Some(bits) = min_entropy
-        && let Some(charset_size) = charset_size_for_style(style, no_ambiguous)
-    {
-        let per_char = (charset_size as f64).log2();
-        if per_char <= 0.0 {
-            return Err("invalid charset size for entropy calculation".into());
-        }
-        let needed = (bits / per_char).ceil() as usize;
-        if needed > length {
+}
+
+impl Default for GeneratorConfig {
+    fn default() -> Self {
+        GeneratorConfig::new(Style::Random)
+    }
+}
+
+impl GeneratorConfig {
+    /// Create a configuration for `style` with the CLI defaults
+    /// (`Style::default_length`).
+    pub fn new(style: Style) -> Self {
+        GeneratorConfig {
+            style,
+            length: style.default_length(),
+            charset: CharsetSpec::default(),
+            policy: Policy::default(),
+            pattern: None,
+            passphrase_format: PassphraseFormat::default(),
+            token_prefix: token::DEFAULT_PREFIX.to_string(),
+            padding: true,
+            encoding: Base64Encoding::default(),
+            length_unit: LengthUnit::default(),
+            wordlist: None,
+            strict_wordlist: false,
+            min_entropy: None,
+        }
+    }
+
+    /// Set the generation style.
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Set the length. Its meaning depends on the style: characters for
+    /// `random`/`pin`, bytes (or characters, see `length_unit`) for
+    /// `hex`/`base64`/`base32`, word count for `passphrase`.
+    pub fn length(mut self, length: usize) -> Self {
+        self.length = length;
+        self
+    }
+
+    /// Remove ambiguous characters (`1lI0O|`) from the `random` pool.
+    pub fn no_ambiguous(mut self, no_ambiguous: bool) -> Self {
+        self.charset.no_ambiguous = no_ambiguous;
+        self
+    }
+
+    /// Compose the `random` pool from `spec` (classes, explicit charset,
+    /// exclusions). Replaces any earlier `no_ambiguous` setting.
+    pub fn charset(mut self, spec: CharsetSpec) -> Self {
+        self.charset = spec;
+        self
+    }
+
+    /// Require minimum counts per character class (`random` style only).
+    ///
+    /// Compliant passwords are sampled uniformly from all compliant strings,
+    /// so the policy does not bias the output. The other rules (length,
+    /// repeats, banned words, entropy) apply to every style by redrawing
+    /// items that break them.
+    pub fn policy(mut self, policy: Policy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Set the template for the `pattern` style (see the `pattern` module for
+    /// the placeholder syntax). `length` is ignored for patterns.
+    pub fn pattern(mut self, template: impl Into<String>) -> Self {
+        self.pattern = Some(template.into());
+        self
+    }
+
+    /// Set separator, capitalization and extras for the `passphrase` style.
+    pub fn passphrase_format(mut self, format: PassphraseFormat) -> Self {
+        self.passphrase_format = format;
+        self
+    }
+
+    /// Set the scanner-friendly prefix of the `token` style (default
+    /// `token::DEFAULT_PREFIX`).
+    pub fn token_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.token_prefix = prefix.into();
+        self
+    }
+
+    /// Keep the `=` padding of `base32` output (default `true`). TOTP secrets
+    /// are usually written without it.
+    pub fn padding(mut self, padding: bool) -> Self {
+        self.padding = padding;
+        self
+    }
+
+    /// Set the alphabet and padding of the `base64` style (default
+    /// `Base64Encoding::Standard`).
+    pub fn encoding(mut self, encoding: Base64Encoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    /// Count `length` in random bytes (the default) or in encoded characters
+    /// for `hex`, `base64` and `base32`. Padded encodings need a whole number
+    /// of blocks (4 characters for base64, 8 for base32).
+    pub fn length_unit(mut self, unit: LengthUnit) -> Self {
+        self.length_unit = unit;
+        self
+    }
+
+    /// Draw passphrase words from a built-in list (`eff-large`, `eff-short1`,
+    /// `eff-short2`) or a newline-delimited file instead of the default
+    /// `eff-large`.
+    pub fn wordlist(mut self, source: impl Into<WordlistSource>) -> Self {
+        self.wordlist = Some(source.into());
+        self
+    }
+
+    /// Refuse wordlists that fail `wordlist::analyze` checks (duplicates,
+    /// non-ASCII words, fewer than `MIN_STRICT_WORDS` words, or prefix
+    /// violations when the separator is empty).
+    pub fn strict_wordlist(mut self, strict: bool) -> Self {
+        self.strict_wordlist = strict;
+        self
+    }
+
+    /// Require at least `bits` of entropy. If the style has a charset hint,
+    /// `build()` increases the length until the target is met.
+    pub fn min_entropy(mut self, bits: f64) -> Self {
+        self.min_entropy = Some(bits);
+        self
+    }
+
+    /// True if `length` counts encoded characters of an encoding style.
+    fn counts_encoded_chars(&self) -> bool {
+        self.length_unit == LengthUnit::Chars
+            && matches!(self.style, Style::Hex | Style::Base64 | Style::Base32)
+    }
+
+    /// Block size in characters when `length` counts characters of a padded
+    /// encoding, which only produces whole blocks without padding.
+    fn padded_block(&self) -> Option<usize> {
+        if !self.counts_encoded_chars() {
+            return None;
+        }
+        match self.style {
+            Style::Base64 if self.encoding.padded() => Some(4),
+            Style::Base32 if self.padding => Some(8),
+            _ => None,
+        }
+    }
+
+    /// Validate the configuration, load the wordlist if the style needs one
+    /// and return a reusable `Generator` backed by the thread-local RNG.
+    ///
+    /// # Errors
+    /// Returns `GenixError::WordlistIo` or `GenixError::EmptyWordlist` for
+    /// wordlist problems, `GenixError::EmptyCharset` when the composed
+    /// `random` pool has no characters left, and `GenixError::InvalidCharset`
+    /// when the entropy target cannot be mapped to a length.
+    /// `GenixError::PolicyUnsatisfiable` is returned when a policy is set for
+    /// a style other than `random`, or cannot be met by the pool and length.
+    /// For `pattern`, a missing or malformed template gives
+    /// `GenixError::InvalidPattern`, and a template weaker than `min_entropy`
+    /// gives `GenixError::InsufficientEntropy`. A character length that a
+    /// padded encoding cannot produce gives `GenixError::InvalidEncoding`.
+    /// A malformed token prefix or a token length of zero gives
+    /// `GenixError::InvalidToken`.
+    pub fn build(self) -> Result<Generator, GenixError> {
+        self.build_with_rng(thread_rng())
+    }
+
+    /// Like `build()`, but draw randomness from `rng` instead of `thread_rng()`.
+    ///
+    /// Pass a `seeded_rng` to get reproducible output in tests.
+    pub fn build_with_rng<R: RngCore + CryptoRng>(
+        self,
+        rng: R,
+    ) -> Result<Generator<R>, GenixError> {
+        let mut length = self.length;
+        let counts_chars = self.counts_encoded_chars();
+        let entropy_at = |length| {
+            if counts_chars {
+                charset_size_for_style(self.style, &self.charset)
+                    .map(|size| length as f64 * (size as f64).log2())
+            } else {
+                style_entropy(self.style, length, &self.charset)
+            }
+        };
+        if let Some(bits) = self.min_entropy
+            && let Some(per_two) = entropy_at(2)
+        {
+            if per_two <= 0.0 {
+                return Err(GenixError::InvalidCharset);
+            }
+            // fixed-format styles cannot grow, so refuse instead of looping
+            if let (Some(now), Some(next)) = (entropy_at(length), entropy_at(length + 1))
+                && now < bits
+                && next <= now
+            {
+                return Err(GenixError::InsufficientEntropy {
+                    required: bits,
+                    available: now,
+                });
+            }
+            while entropy_at(length).is_some_and(|available| available < bits) {
+                length += 1;
+            }
+            if self.style == Style::Bip39 {
+                // valid word counts are the multiples of three from 12 to 24
+                length = length.next_multiple_of(3);
+            }
+            if let Some(block) = self.padded_block() {
+                length = length.next_multiple_of(block);
+            }
+        }
+        if let Some(block) = self.padded_block()
+            && !length.is_multiple_of(block)
+        {
+            return Err(GenixError::InvalidEncoding(format!(
+                "padded {} output comes in blocks of {} characters, so {} characters is impossible; use an unpadded encoding",
+                self.style, block, length
+            )));
+        }
+        if self.style == Style::Bip39 {
+            bip39::entropy_bits(length)?;
+        }
+        if self.style == Style::Token {
+            token::validate_prefix(&self.token_prefix)?;
+            if length == 0 {
+                // `validate_token` refuses a body that is only the checksum
+                return Err(GenixError::InvalidToken(
+                    "a token needs at least one random character".into(),
+                ));
+            }
+        }
+
+        let pool = if matches!(self.style, Style::Random | Style::Nanoid) {
+            let pool = if self.style == Style::Nanoid {
+                nanoid_alphabet(&self.charset)
+            } else {
+                self.charset.pool()
+            };
+            if pool.is_empty() {
+                return Err(GenixError::EmptyCharset);
+            }
+            pool
+        } else {
+            Vec::new()
+        };
+
+        let sampler = if self.policy.required_total() == 0 {
+            None
+        } else {
+            if self.style != Style::Random {
+                return Err(GenixError::PolicyUnsatisfiable(format!(
+                    "class requirements only apply to the random style, not {}",
+                    self.style
+                )));
+            }
+            self.policy.validate(&pool, length)?;
+            if let Some(bits) = self.min_entropy {
+                // The constrained space is smaller than pool^length, so the
+                // plain per-character estimate may fall short.
+                while policy_entropy(&pool, length, &self.policy) < bits {
+                    length += 1;
+                }
+            }
+            Some(PolicySampler::new(&self.policy, &pool, length))
+        };
+
+        if length > self.length
+            && let Some(bits) = self.min_entropy
+        {
             eprintln!(
                 "info: increasing length from {} to {} to satisfy min-entropy {} bits",
-                length, needed, bits
+                self.length, length, bits
             );
-            length = needed;
         }
-    }
 
-    match style {
-        "random" => Ok((0..count)
-            .map(|_| random_string(length, no_ambiguous))
-            .collect()),
-        "pin" => Ok((0..count).map(|_| pin_string(length)).collect()),
-        "hex" => Ok((0..count).map(|_| hex_string(length)).collect()),
-        "base64" => Ok((0..count).map(|_| base64_string(length)).collect()),
-        "passphrase" => {
-            let words = load_wordlist(wordlist)?;
+        let pattern = match self.style {
+            Style::Pattern => {
+                let template = self.pattern.as_deref().ok_or_else(|| {
+                    GenixError::InvalidPattern("the pattern style requires a pattern".into())
+                })?;
+                Some(Pattern::parse(template)?)
+            }
+            Style::Pronounceable => Some(Pattern::pronounceable(length)),
+            _ => None,
+        };
+
+        let needs_words =
+            self.style == Style::Passphrase || pattern.as_ref().is_some_and(Pattern::uses_words);
+        let (words, unique_words) = if needs_words {
+            let words = load_wordlist(self.wordlist.as_ref())?;
             if words.is_empty() {
-                return Err("wordlist is empty".into());
+                return Err(GenixError::EmptyWordlist);
             }
-            Ok((0..count)
-                .map(|_| passphrase_from(&words, length))
-                .collect())
+            let report = analyze(&words);
+            if self.strict_wordlist {
+                let format = &self.passphrase_format;
+                // random separators are never empty, so words cannot run together
+                let separator = if format.random_separator {
+                    RANDOM_SEPARATORS
+                } else {
+                    format.separator.as_str()
+                };
+                let problems = report.problems(separator);
+                if !problems.is_empty() {
+                    return Err(GenixError::WordlistRejected(problems.join("; ")));
+                }
+            }
+            (words, report.unique)
+        } else {
+            (Vec::new(), 0)
+        };
+
+        // A pattern has a fixed shape, so its entropy cannot be raised by
+        // lengthening it; refuse instead of silently falling short.
+        if let (Style::Pattern, Some(pattern), Some(bits)) =
+            (self.style, &pattern, self.min_entropy)
+        {
+            let available = pattern_entropy(pattern, unique_words);
+            if available < bits {
+                return Err(GenixError::InsufficientEntropy {
+                    required: bits,
+                    available,
+                });
+            }
+        }
+
+        // min_entropy is held against the exact entropy below, not per item
+        let item_rules = Policy {
+            min_entropy: None,
+            ..self.policy.clone()
+        };
+        let checker = if item_rules.has_string_rules() {
+            let fixed_chars =
+                matches!(self.style, Style::Random | Style::Pin | Style::Nanoid) || counts_chars;
+            if fixed_chars && length < self.policy.min_length {
+                return Err(GenixError::PolicyUnsatisfiable(format!(
+                    "policy requires {} characters but length is {}",
+                    self.policy.min_length, length
+                )));
+            }
+            Some(item_rules.checker()?)
+        } else {
+            None
+        };
+
+        let generator = Generator {
+            config: self,
+            length,
+            pool,
+            sampler,
+            checker,
+            pattern,
+            words,
+            unique_words,
+            rng,
+        };
+        if let Some(bits) = generator.config.policy.min_entropy
+            && generator.entropy_bits() < bits
+        {
+            return Err(GenixError::PolicyUnsatisfiable(format!(
+                "policy requires {} bits but the generator produces {:.2}",
+                bits,
+                generator.entropy_bits()
+            )));
         }
-        _ => Err(format!("unknown style: {}", style)),
+        Ok(generator)
     }
 }
 
-/// Generate a random string using the default printable set.
+/// Candidates a policy may reject in a row before `generate_one` gives up.
+const MAX_POLICY_ATTEMPTS: usize = 100_000;
+
+/// A configured generator holding its loaded wordlist and RNG.
 ///
-/// This helper is intentionally small and deterministic in its contract: it
-/// returns a string of length `len`, optionally filtering ambiguous chars.
-fn random_string(len: usize, no_ambiguous: bool) -> String {
-    let mut rng = thread_rng();
-    let mut pool: Vec<char> = DEFAULT_PRINTABLE.chars().collect();
-    if no_ambiguous {
-        pool.retain(|c| !AMBIGUOUS.contains(*c));
+/// Created by `GeneratorConfig::build()` or `build_with_rng()`. Reusing one
+/// `Generator` avoids reloading the wordlist for every item.
+#[derive(Debug)]
+pub struct Generator<R = ThreadRng> {
+    config: GeneratorConfig,
+    length: usize,
+    pool: Vec<char>,
+    sampler: Option<PolicySampler>,
+    checker: Option<PolicyChecker>,
+    pattern: Option<Pattern>,
+    words: Vec<String>,
+    /// Distinct words, as counted by `wordlist::analyze`; duplicate lines
+    /// add no entropy.
+    unique_words: usize,
+    rng: R,
+}
+
+impl<R: RngCore + CryptoRng> Generator<R> {
+    /// The effective length, after any `min_entropy` adjustment.
+    pub fn length(&self) -> usize {
+        self.length
+    }
+
+    /// The configured style.
+    pub fn style(&self) -> Style {
+        self.config.style
+    }
+
+    /// Exact entropy (bits) of each generated item, for the effective length.
+    ///
+    /// Items redrawn for a policy's repetition or banned-word rules are left
+    /// out of the space, so with those rules this is an upper bound.
+    pub fn entropy_bits(&self) -> f64 {
+        let config = &self.config;
+        match config.style {
+            Style::Random if self.sampler.is_some() => {
+                policy_entropy(&self.pool, self.length, &config.policy)
+            }
+            Style::Passphrase => {
+                self.length as f64 * (self.unique_words as f64).log2()
+                    + config.passphrase_format.extra_entropy(self.length)
+            }
+            Style::Pattern | Style::Pronounceable => self
+                .pattern
+                .as_ref()
+                .map_or(0.0, |pattern| pattern_entropy(pattern, self.unique_words)),
+            style if config.counts_encoded_chars() => (self.length * bits_per_char(style)) as f64,
+            style => style_entropy(style, self.length, &config.charset).unwrap_or(0.0),
+        }
     }
-    if pool.is_empty() {
-        return String::new();
+
+    /// Generate a single item.
+    ///
+    /// Items that break the policy's length, repetition or banned-word rules
+    /// are discarded and redrawn.
+    ///
+    /// # Errors
+    /// Returns `GenixError::PolicyUnsatisfiable` if the policy rejects
+    /// `MAX_POLICY_ATTEMPTS` items in a row, i.e. the configuration can
+    /// (almost) never comply.
+    pub fn generate_one(&mut self) -> Result<String, GenixError> {
+        let Some(checker) = self.checker.take() else {
+            return Ok(self.candidate());
+        };
+        let style = self.config.style;
+        let options = EstimateOptions::default();
+        let item = (0..MAX_POLICY_ATTEMPTS)
+            .map(|_| self.candidate())
+            .find(|item| checker.check(item, style, &options).passed());
+        self.checker = Some(checker);
+        item.ok_or_else(|| {
+            GenixError::PolicyUnsatisfiable(format!(
+                "the policy rejected {} generated items in a row",
+                MAX_POLICY_ATTEMPTS
+            ))
+        })
+    }
+
+    /// Draw one item, ignoring the policy's string rules.
+    fn candidate(&mut self) -> String {
+        let length = self.length;
+        match self.config.style {
+            Style::Random => match &self.sampler {
+                Some(sampler) => sampler.sample(&mut self.rng),
+                None => random_string(&mut self.rng, &self.pool, length),
+            },
+            Style::Nanoid => random_string(&mut self.rng, &self.pool, length),
+            Style::Token => token::generate_token(&mut self.rng, &self.config.token_prefix, length),
+            Style::Uuid4 => identifier::uuid4(&mut self.rng),
+            Style::Uuid7 => identifier::uuid7(&mut self.rng, identifier::unix_millis()),
+            Style::Ulid => identifier::ulid(&mut self.rng, identifier::unix_millis()),
+            Style::Pin => pin_string(&mut self.rng, length),
+            Style::Hex | Style::Base64 | Style::Base32 => self.encoded_string(),
+            Style::Passphrase => passphrase_from(
+                &mut self.rng,
+                &self.words,
+                length,
+                &self.config.passphrase_format,
+            ),
+            Style::Bip39 => bip39::generate_mnemonic(&mut self.rng, length)
+                .expect("word count validated by build"),
+            Style::Pattern | Style::Pronounceable => match &self.pattern {
+                Some(pattern) => pattern_string(&mut self.rng, pattern, &self.words),
+                None => String::new(),
+            },
+        }
+    }
+
+    /// Encode random bytes for `hex`/`base64`/`base32`, honouring the length unit.
+    fn encoded_string(&mut self) -> String {
+        let counts_chars = self.config.counts_encoded_chars();
+        let bytes = if counts_chars {
+            // enough bytes that the first `length` characters are all random
+            (self.length * bits_per_char(self.config.style)).div_ceil(8)
+        } else {
+            self.length
+        };
+        let mut out = match self.config.style {
+            Style::Hex => hex_string(&mut self.rng, bytes),
+            Style::Base64 => base64_string(&mut self.rng, bytes, self.config.encoding),
+            _ => base32_string(&mut self.rng, bytes, self.config.padding),
+        };
+        if counts_chars {
+            out.truncate(self.length);
+        }
+        out
+    }
+
+    /// Return an endless iterator of generated items; use `take(n)` to bound it.
+    /// Items fail only as `generate_one` does.
+    pub fn generate_iter(&mut self) -> impl Iterator<Item = Result<String, GenixError>> + '_ {
+        std::iter::repeat_with(move || self.generate_one())
+    }
+}
+
+/// Generate `count` items from `config`.
+///
+/// This is a convenience wrapper that builds a one-off `Generator`; build the
+/// generator yourself when producing items in several batches.
+///
+/// # Errors
+/// Returns the same errors as `GeneratorConfig::build()` and
+/// `Generator::generate_one()`.
+pub fn generate_many(config: &GeneratorConfig, count: usize) -> Result<Vec<String>, GenixError> {
+    let mut generator = config.clone().build()?;
+    generator.generate_iter().take(count).collect()
+}
+
+/// Create a deterministic ChaCha20 RNG from a hex seed of up to 32 bytes.
+///
+/// Shorter seeds are zero-padded, so `"00"` and `"0000"` give the same stream.
+/// **Insecure by design:** anyone who knows the seed can recompute every
+/// output. Use it only for tests and reproducible fixtures, never for real
+/// credentials.
+///
+/// # Errors
+/// Returns `GenixError::InvalidSeed` if `hex` is empty, has an odd number of
+/// digits, contains non-hex characters or is longer than 64 digits.
+pub fn seeded_rng(hex: &str) -> Result<ChaCha20Rng, GenixError> {
+    let invalid = |reason: &str| GenixError::InvalidSeed(reason.to_string());
+    if hex.is_empty() {
+        return Err(invalid("seed is empty"));
+    }
+    if !hex.len().is_multiple_of(2) {
+        return Err(invalid("seed must have an even number of hex digits"));
+    }
+    if hex.len() > 64 {
+        return Err(invalid("seed must be at most 32 bytes (64 hex digits)"));
+    }
+    if !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(invalid("seed is not valid hex"));
+    }
+    let mut seed = [0u8; 32];
+    for (i, pair) in hex.as_bytes().chunks(2).enumerate() {
+        let digits = std::str::from_utf8(pair).expect("ascii hex digits");
+        seed[i] = u8::from_str_radix(digits, 16).expect("validated hex digits");
     }
-    let dist = Uniform::from(0..pool.len());
-    (0..len).map(|_| pool[rng.sample(dist)]).collect()
+    Ok(ChaCha20Rng::from_seed(seed))
+}
+
+/// Generate a random string drawn uniformly from `pool`.
+///
+/// This helper is intentionally small and deterministic in its contract: it
+/// returns a string of length `len`. `pool` must be non-empty (`build()`
+/// rejects empty pools).
+fn random_string<R: RngCore + CryptoRng>(rng: &mut R, pool: &[char], len: usize) -> String {
+    let dist = index_dist(pool.len());
+    (0..len).map(|_| pool[rng.sample(dist) as usize]).collect()
 }
 
 /// Generate a numeric PIN of length `len`.
-fn pin_string(len: usize) -> String {
-    let mut rng = thread_rng();
+fn pin_string<R: RngCore + CryptoRng>(rng: &mut R, len: usize) -> String {
     let dist = Uniform::from(0..10);
     (0..len)
         .map(|_| char::from(b'0' + rng.sample(dist) as u8))
@@ -111,91 +833,555 @@ fn pin_string(len: usize) -> String {
 }
 
 /// Generate a hex string representing `bytes` random bytes.
-fn hex_string(bytes: usize) -> String {
-    let mut rng = thread_rng();
+fn hex_string<R: RngCore + CryptoRng>(rng: &mut R, bytes: usize) -> String {
     let mut buf = vec![0u8; bytes];
     rng.fill(&mut buf[..]);
     buf.iter().map(|b| format!("{:02x}", b)).collect()
 }
 
 /// Generate a base64 encoding of `bytes` random bytes.
-fn base64_string(bytes: usize) -> String {
-    let mut rng = thread_rng();
+fn base64_string<R: RngCore + CryptoRng>(
+    rng: &mut R,
+    bytes: usize,
+    encoding: Base64Encoding,
+) -> String {
     let mut buf = vec![0u8; bytes];
     rng.fill(&mut buf[..]);
-    general_purpose::STANDARD.encode(&buf)
-}
-
-/// Load a newline-delimited wordlist from `path` or return a built-in list.
-fn load_wordlist(path: Option<&str>) -> Result<Vec<String>, String> {
-    if let Some(p) = path {
-        let file = File::open(p).map_err(|e| format!("failed to open wordlist {}: {}", p, e))?;
-        let reader = BufReader::new(file);
-        Ok(reader
-            .lines()
-            .map_while(Result::ok)
-            .map(|s| s.trim().to_string())
-            .filter(|s| !s.is_empty())
-            .collect())
-    } else {
-        Ok(vec![
-            "alpha".into(),
-            "bravo".into(),
-            "charlie".into(),
-            "delta".into(),
-            "echo".into(),
-            "foxtrot".into(),
-            "golf".into(),
-            "hotel".into(),
-            "india".into(),
-            "juliet".into(),
-        ])
-    }
-}
-
-/// Build a dash-separated passphrase from `target_words` randomly sampled words.
-fn passphrase_from(words: &[String], target_words: usize) -> String {
-    let mut rng = thread_rng();
-    let dist = Uniform::from(0..words.len());
-    (0..target_words)
-        .map(|_| words[rng.sample(dist)].clone())
-        .collect::<Vec<_>>()
-        .join("-")
+    encoding.encode(&buf)
+}
+
+/// Generate an RFC 4648 base32 encoding of `bytes` random bytes.
+fn base32_string<R: RngCore + CryptoRng>(rng: &mut R, bytes: usize, padding: bool) -> String {
+    let mut buf = vec![0u8; bytes];
+    rng.fill(&mut buf[..]);
+    totp::encode_base32(&buf, padding)
+}
+
+/// Expand `pattern`, drawing each placeholder uniformly from its class.
+fn pattern_string<R: RngCore + CryptoRng>(
+    rng: &mut R,
+    pattern: &Pattern,
+    words: &[String],
+) -> String {
+    let mut out = String::new();
+    for token in pattern.tokens() {
+        match token {
+            Token::Literal(c) => out.push(*c),
+            Token::Class(set) => out.push(set[rng.sample(index_dist(set.len())) as usize]),
+            Token::Word { capitalize } => {
+                let word = &words[rng.sample(index_dist(words.len())) as usize];
+                if *capitalize {
+                    let mut chars = word.chars();
+                    if let Some(first) = chars.next() {
+                        out.extend(first.to_uppercase());
+                        out.push_str(chars.as_str());
+                    }
+                } else {
+                    out.push_str(word);
+                }
+            }
+        }
+    }
+    out
+}
+
+/// Bits carried by one encoded character of `hex`, `base64` or `base32`.
+fn bits_per_char(style: Style) -> usize {
+    match style {
+        Style::Hex => 4,
+        Style::Base64 => 6,
+        _ => 5,
+    }
+}
+
+/// Uniform distribution over `0..len` sampled as `u32`.
+///
+/// `Uniform<usize>` draws 32 or 64 bits depending on the target, which would
+/// make seeded output differ between platforms; sampling `u32` keeps it stable.
+pub(crate) fn index_dist(len: usize) -> Uniform<u32> {
+    Uniform::from(0..len as u32)
+}
+
+/// Build a passphrase from `target_words` randomly sampled words, formatted
+/// according to `format`.
+///
+/// Words are drawn first, then capitalization, the appended digit and symbol,
+/// and finally the separators, so the default format consumes exactly the
+/// same randomness as plain `word-word-word` output.
+fn passphrase_from<R: RngCore + CryptoRng>(
+    rng: &mut R,
+    words: &[String],
+    target_words: usize,
+    format: &PassphraseFormat,
+) -> String {
+    let dist = index_dist(words.len());
+    let mut chosen: Vec<String> = (0..target_words)
+        .map(|_| words[rng.sample(dist) as usize].clone())
+        .collect();
+
+    for word in chosen.iter_mut() {
+        let capitalize_first = match format.capitalize {
+            Capitalize::None => false,
+            Capitalize::First => true,
+            Capitalize::All => {
+                *word = word.to_uppercase();
+                false
+            }
+            Capitalize::Random => rng.gen_bool(0.5),
+        };
+        if capitalize_first {
+            let mut chars = word.chars();
+            if let Some(first) = chars.next() {
+                *word = first.to_uppercase().chain(chars).collect();
+            }
+        }
+    }
+
+    if !chosen.is_empty() {
+        let positions = index_dist(chosen.len());
+        if format.add_digit {
+            let target = rng.sample(positions) as usize;
+            let digit = pick_char(rng, DIGITS);
+            chosen[target].push(digit);
+        }
+        if format.add_symbol {
+            let target = rng.sample(positions) as usize;
+            let symbol = pick_char(rng, SYMBOLS);
+            chosen[target].push(symbol);
+        }
+    }
+
+    let mut out = String::new();
+    for (i, word) in chosen.iter().enumerate() {
+        if i > 0 {
+            if format.random_separator {
+                out.push(pick_char(rng, RANDOM_SEPARATORS));
+            } else {
+                out.push_str(&format.separator);
+            }
+        }
+        out.push_str(word);
+    }
+    out
+}
+
+/// Pick one character of `set` uniformly.
+fn pick_char<R: RngCore + CryptoRng>(rng: &mut R, set: &str) -> char {
+    let chars: Vec<char> = set.chars().collect();
+    chars[rng.sample(index_dist(chars.len())) as usize]
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::passphrase::ParsedPassphrase;
 
     #[test]
     fn test_random_length() {
-        let out = random_string(16, false);
+        let pool = CharsetSpec::default().pool();
+        let out = random_string(&mut thread_rng(), &pool, 16);
         assert_eq!(out.len(), 16);
     }
 
     #[test]
     fn test_hex_length() {
-        let s = hex_string(4);
+        let s = hex_string(&mut thread_rng(), 4);
         assert_eq!(s.len(), 8);
     }
 
     #[test]
     fn test_base64() {
-        let s = base64_string(3);
+        let s = base64_string(&mut thread_rng(), 3, Base64Encoding::Standard);
         assert!(s.len() >= 4);
     }
 
+    #[test]
+    fn test_generator_entropy_bits() {
+        let generator = GeneratorConfig::new(Style::Hex).length(16).build().unwrap();
+        assert_eq!(generator.entropy_bits(), 128.0);
+        let generator = GeneratorConfig::new(Style::Base32)
+            .length(10)
+            .length_unit(LengthUnit::Chars)
+            .padding(false)
+            .build()
+            .unwrap();
+        assert_eq!(generator.entropy_bits(), 50.0);
+        let generator = GeneratorConfig::new(Style::Passphrase)
+            .length(4)
+            .wordlist("assets/eff_sample.txt")
+            .build()
+            .unwrap();
+        let words = load_wordlist(Some(&WordlistSource::from("assets/eff_sample.txt"))).unwrap();
+        assert!((generator.entropy_bits() - 4.0 * (words.len() as f64).log2()).abs() < 1e-9);
+        // duplicate lines (in any case) do not add words
+        let path = std::env::temp_dir().join(format!("genix-dupes-{}.txt", std::process::id()));
+        std::fs::write(&path, "apple\nbanana\napple\nApple\n").unwrap();
+        let generator = GeneratorConfig::new(Style::Passphrase)
+            .length(3)
+            .wordlist(path.display().to_string())
+            .build()
+            .unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(generator.entropy_bits(), 3.0);
+    }
+
+    #[test]
+    fn test_length_in_chars() {
+        let config = GeneratorConfig::new(Style::Base64)
+            .length(43)
+            .encoding(Base64Encoding::UrlNoPad)
+            .length_unit(LengthUnit::Chars);
+        let token = generate_many(&config, 1).unwrap().remove(0);
+        assert_eq!(token.len(), 43);
+        assert!(
+            token
+                .bytes()
+                .all(|b| b.is_ascii_alphanumeric() || b"-_".contains(&b))
+        );
+
+        let hex = generate_many(&config.clone().style(Style::Hex).length(7), 1).unwrap();
+        assert_eq!(hex[0].len(), 7);
+
+        let padded = config.clone().encoding(Base64Encoding::Url).build();
+        assert!(matches!(padded, Err(GenixError::InvalidEncoding(_))));
+
+        // 128 bits at 6 bits per char rounds up to 22 chars, then to a block of 4
+        let sized = GeneratorConfig::new(Style::Base64)
+            .length(4)
+            .length_unit(LengthUnit::Chars)
+            .min_entropy(128.0)
+            .build()
+            .unwrap();
+        assert_eq!(sized.length(), 24);
+    }
+
+    #[test]
+    fn test_base32_padding() {
+        let padded = base32_string(&mut thread_rng(), 20, true);
+        assert_eq!(padded.len(), 32);
+        let unpadded = base32_string(&mut thread_rng(), 3, false);
+        assert_eq!(unpadded.len(), 5);
+        assert_eq!(base32_string(&mut thread_rng(), 3, true).len(), 8);
+    }
+
     #[test]
     fn test_passphrase_default() {
         let words = load_wordlist(None).unwrap();
-        let p = passphrase_from(&words, 4);
+        let p = passphrase_from(&mut thread_rng(), &words, 4, &PassphraseFormat::default());
         assert!(p.split('-').count() == 4);
     }
 
     #[test]
     fn test_min_entropy_increases_length() {
-        let res = generate_many("pin", 6, 1, None, false, Some(40.0)).unwrap();
+        let config = GeneratorConfig::new(Style::Pin).length(6).min_entropy(40.0);
+        let res = generate_many(&config, 1).unwrap();
         assert_eq!(res.len(), 1);
         assert!(res[0].len() >= 13);
     }
+
+    #[test]
+    fn test_style_round_trip() {
+        for style in Style::ALL {
+            assert_eq!(style.to_string().parse::<Style>().unwrap(), style);
+        }
+        assert!(matches!(
+            "bogus".parse::<Style>(),
+            Err(GenixError::UnknownStyle(s)) if s == "bogus"
+        ));
+    }
+
+    #[test]
+    fn test_missing_wordlist_error() {
+        let err = GeneratorConfig::new(Style::Passphrase)
+            .wordlist("/nonexistent/genix-wordlist.txt")
+            .build()
+            .err()
+            .unwrap();
+        assert!(matches!(err, GenixError::WordlistIo { .. }));
+    }
+
+    #[test]
+    fn test_strict_wordlist_rejects_small_list() {
+        let config = GeneratorConfig::new(Style::Passphrase).wordlist("assets/eff_sample.txt");
+        assert!(generate_many(&config, 1).is_ok());
+        let err = generate_many(&config.strict_wordlist(true), 1).unwrap_err();
+        assert!(matches!(err, GenixError::WordlistRejected(_)));
+    }
+
+    #[test]
+    fn test_seeded_generators_match() {
+        let config = GeneratorConfig::new(Style::Random).length(24);
+        let mut a = config
+            .clone()
+            .build_with_rng(seeded_rng("2a").unwrap())
+            .unwrap();
+        let mut b = config.build_with_rng(seeded_rng("2a00").unwrap()).unwrap();
+        let first: Vec<String> = a.generate_iter().take(5).map(Result::unwrap).collect();
+        let second: Vec<String> = b.generate_iter().take(5).map(Result::unwrap).collect();
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn test_seeded_rng_rejects_bad_hex() {
+        for bad in ["", "abc", "zz", "+f", &"00".repeat(33)] {
+            assert!(matches!(seeded_rng(bad), Err(GenixError::InvalidSeed(_))));
+        }
+    }
+
+    #[test]
+    fn test_custom_charset_is_respected() {
+        let spec = CharsetSpec {
+            symbols_set: Some("-_".into()),
+            exclude: "abc".into(),
+            ..CharsetSpec::default()
+        };
+        let allowed = spec.pool();
+        let res = generate_many(&GeneratorConfig::new(Style::Random).charset(spec), 20).unwrap();
+        assert!(
+            res.iter()
+                .flat_map(|s| s.chars())
+                .all(|c| allowed.contains(&c))
+        );
+
+        let empty = CharsetSpec {
+            charset: Some("xy".into()),
+            exclude: "xy".into(),
+            ..CharsetSpec::default()
+        };
+        let err = GeneratorConfig::new(Style::Random)
+            .charset(empty)
+            .build()
+            .err()
+            .unwrap();
+        assert!(matches!(err, GenixError::EmptyCharset));
+    }
+
+    #[test]
+    fn test_policy_generation_complies() {
+        let policy = Policy {
+            min_upper: 1,
+            min_digit: 2,
+            min_symbol: 1,
+            ..Policy::default()
+        };
+        let config = GeneratorConfig::new(Style::Random)
+            .length(8)
+            .policy(policy.clone());
+        let res = generate_many(&config, 50).unwrap();
+        assert!(res.iter().all(|s| policy.is_satisfied_by(s)));
+
+        let err = GeneratorConfig::new(Style::Pin)
+            .policy(policy)
+            .build()
+            .err()
+            .unwrap();
+        assert!(matches!(err, GenixError::PolicyUnsatisfiable(_)));
+    }
+
+    #[test]
+    fn test_policy_rules_constrain_generation() {
+        let policy = Policy {
+            max_repeated: Some(1),
+            banned_substrings: vec!["0".into()],
+            ..Policy::default()
+        };
+        let config = GeneratorConfig::new(Style::Pin)
+            .length(8)
+            .policy(policy.clone());
+        let res = generate_many(&config, 50).unwrap();
+        let checker = policy.checker().unwrap();
+        let options = EstimateOptions::default();
+        assert!(
+            res.iter()
+                .all(|s| checker.check(s, Style::Pin, &options).passed()),
+            "{:?}",
+            res
+        );
+
+        let no_digits = Policy {
+            banned_substrings: ('0'..='9').map(String::from).collect(),
+            ..Policy::default()
+        };
+        let mut generator = GeneratorConfig::new(Style::Pin)
+            .length(4)
+            .policy(no_digits)
+            .build()
+            .unwrap();
+        assert!(matches!(
+            generator.generate_one(),
+            Err(GenixError::PolicyUnsatisfiable(_))
+        ));
+
+        let too_short = Policy {
+            min_length: 12,
+            ..Policy::default()
+        };
+        let config = GeneratorConfig::new(Style::Random)
+            .length(8)
+            .policy(too_short);
+        assert!(matches!(
+            config.build(),
+            Err(GenixError::PolicyUnsatisfiable(_))
+        ));
+        let too_weak = Policy {
+            min_entropy: Some(40.0),
+            ..Policy::default()
+        };
+        let config = GeneratorConfig::new(Style::Pin).length(6).policy(too_weak);
+        assert!(matches!(
+            config.build(),
+            Err(GenixError::PolicyUnsatisfiable(_))
+        ));
+    }
+
+    #[test]
+    fn test_policy_min_entropy_uses_constrained_space() {
+        let policy = Policy {
+            min_digit: 3,
+            ..Policy::default()
+        };
+        let pool = CharsetSpec::default().pool();
+        let generator = GeneratorConfig::new(Style::Random)
+            .length(4)
+            .policy(policy.clone())
+            .min_entropy(60.0)
+            .build()
+            .unwrap();
+        let length = generator.length();
+        assert!(policy_entropy(&pool, length, &policy) >= 60.0);
+        assert!(policy_entropy(&pool, length - 1, &policy) < 60.0);
+    }
+
+    #[test]
+    fn test_pattern_shape() {
+        let config = GeneratorConfig::new(Style::Pattern).pattern("XXXX-9999-Ww");
+        for item in generate_many(&config, 20).unwrap() {
+            let (key, rest) = item.split_at(4);
+            assert!(
+                key.chars()
+                    .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit())
+            );
+            assert!(rest.starts_with('-'));
+            assert!(rest[1..5].chars().all(|c| c.is_ascii_digit()));
+            assert!(rest[6..].chars().next().unwrap().is_ascii_uppercase());
+        }
+
+        let missing = GeneratorConfig::new(Style::Pattern).build().err().unwrap();
+        assert!(matches!(missing, GenixError::InvalidPattern(_)));
+        let weak = GeneratorConfig::new(Style::Pattern)
+            .pattern("9999")
+            .min_entropy(20.0)
+            .build()
+            .err()
+            .unwrap();
+        assert!(matches!(weak, GenixError::InsufficientEntropy { .. }));
+    }
+
+    #[test]
+    fn test_pronounceable_min_entropy() {
+        let generator = GeneratorConfig::new(Style::Pronounceable)
+            .length(4)
+            .min_entropy(50.0)
+            .build()
+            .unwrap();
+        let length = generator.length();
+        let spec = CharsetSpec::default();
+        assert!(style_entropy(Style::Pronounceable, length, &spec).unwrap() >= 50.0);
+        assert!(style_entropy(Style::Pronounceable, length - 1, &spec).unwrap() < 50.0);
+
+        let config = GeneratorConfig::new(Style::Pronounceable).length(9);
+        for item in generate_many(&config, 20).unwrap() {
+            assert_eq!(item.len(), 9);
+            for (i, c) in item.chars().enumerate() {
+                assert_eq!("aeiou".contains(c), i % 2 == 1, "{}", item);
+            }
+        }
+    }
+
+    #[test]
+    fn test_passphrase_format_round_trips_through_parser() {
+        let format = PassphraseFormat {
+            separator: " ".into(),
+            capitalize: Capitalize::First,
+            add_digit: true,
+            add_symbol: true,
+            ..PassphraseFormat::default()
+        };
+        let config = GeneratorConfig::new(Style::Passphrase)
+            .length(4)
+            .passphrase_format(format.clone());
+        for item in generate_many(&config, 20).unwrap() {
+            let parsed = ParsedPassphrase::parse(&item, Some(" "));
+            assert_eq!(parsed.words.len(), 4, "{}", item);
+            assert_eq!(parsed.capitalization(), Capitalize::First);
+            assert_eq!((parsed.digits, parsed.symbols), (1, 1), "{}", item);
+            assert!((parsed.extra_entropy() - format.extra_entropy(4)).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_bip39_style() {
+        let config = GeneratorConfig::new(Style::Bip39).length(24);
+        let phrase = &generate_many(&config, 1).unwrap()[0];
+        assert_eq!(bip39::validate_mnemonic(phrase).unwrap(), 256);
+        let sized = GeneratorConfig::new(Style::Bip39)
+            .length(12)
+            .min_entropy(150.0);
+        assert_eq!(sized.build().unwrap().length(), 15);
+        let bad = GeneratorConfig::new(Style::Bip39).length(13).build();
+        assert!(matches!(bad, Err(GenixError::Bip39WordCount(13))));
+    }
+
+    #[test]
+    fn test_identifier_styles() {
+        let nanoid = generate_many(&GeneratorConfig::new(Style::Nanoid), 1).unwrap();
+        assert_eq!(nanoid[0].len(), NANOID_LENGTH);
+        let custom = GeneratorConfig::new(Style::Nanoid)
+            .length(8)
+            .charset(CharsetSpec {
+                charset: Some("ab".into()),
+                ..CharsetSpec::default()
+            });
+        let item = &generate_many(&custom, 1).unwrap()[0];
+        assert!(item.len() == 8 && item.chars().all(|c| c == 'a' || c == 'b'));
+
+        let uuid = generate_many(&GeneratorConfig::new(Style::Uuid4), 1).unwrap();
+        assert_eq!(uuid[0].len(), 36);
+        let too_strong = GeneratorConfig::new(Style::Uuid7)
+            .min_entropy(100.0)
+            .build();
+        assert!(matches!(
+            too_strong,
+            Err(GenixError::InsufficientEntropy { .. })
+        ));
+    }
+
+    #[test]
+    fn test_token_style() {
+        let config = GeneratorConfig::new(Style::Token).token_prefix("ci");
+        let token = &generate_many(&config, 1).unwrap()[0];
+        assert!(token.starts_with("ci_"));
+        assert!(token::validate_token(token).is_ok());
+        let bad = GeneratorConfig::new(Style::Token)
+            .token_prefix("no-dash")
+            .build();
+        assert!(matches!(bad, Err(GenixError::InvalidToken(_))));
+        let empty = GeneratorConfig::new(Style::Token).length(0).build();
+        assert!(matches!(empty, Err(GenixError::InvalidToken(_))));
+        let shortest = &generate_many(&config.length(1), 1).unwrap()[0];
+        assert!(token::validate_token(shortest).is_ok());
+    }
+
+    #[test]
+    fn test_generator_reuse() {
+        let mut generator = GeneratorConfig::new(Style::Hex).length(8).build().unwrap();
+        let items: Vec<String> = generator
+            .generate_iter()
+            .take(3)
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(items.len(), 3);
+        assert!(items.iter().all(|s| s.len() == 16));
+        assert_eq!(generator.generate_one().unwrap().len(), 16);
+    }
 }
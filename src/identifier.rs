@@ -0,0 +1,145 @@
+//! Identifier styles: UUIDv4, UUIDv7, ULID and NanoID.
+//!
+//! Identifiers are not secrets, but they share the generator's RNG plumbing
+//! and entropy reporting. Only some of their bits are random:
+//!
+//! | Style    | Format                         | Random bits              |
+//! |----------|--------------------------------|--------------------------|
+//! | `uuid4`  | RFC 9562 version 4             | 122                      |
+//! | `uuid7`  | RFC 9562 version 7 (ms time)   | 74                       |
+//! | `ulid`   | 26 Crockford base32 chars      | 80                       |
+//! | `nanoid` | `length` chars of an alphabet  | `length * log2(alphabet)`|
+//!
+//! `uuid7` and `ulid` start with the current Unix time in milliseconds, so
+//! they sort by creation time; their output is not reproducible under
+//! `--seed`.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use rand::{CryptoRng, RngCore};
+
+use crate::charset::CharsetSpec;
+
+/// Random bits in a version 4 UUID (128 minus 4 version and 2 variant bits).
+pub const UUID4_RANDOM_BITS: f64 = 122.0;
+/// Random bits in a version 7 UUID (UUIDv4's 122 minus the 48-bit timestamp).
+pub const UUID7_RANDOM_BITS: f64 = 74.0;
+/// Random bits in a ULID (128 minus the 48-bit timestamp).
+pub const ULID_RANDOM_BITS: f64 = 80.0;
+/// Default NanoID alphabet (URL-safe, 64 characters).
+pub const NANOID_ALPHABET: &str =
+    "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789_-";
+/// Default NanoID length (126 bits with the default alphabet).
+pub const NANOID_LENGTH: usize = 21;
+
+const CROCKFORD: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+
+/// The NanoID alphabet for `spec`: its explicit `charset`, otherwise
+/// `NANOID_ALPHABET`, minus `exclude` and (with `no_ambiguous`) `AMBIGUOUS`.
+pub fn nanoid_alphabet(spec: &CharsetSpec) -> Vec<char> {
+    let charset = spec.charset.as_deref().unwrap_or(NANOID_ALPHABET);
+    CharsetSpec {
+        charset: Some(charset.to_string()),
+        ..spec.clone()
+    }
+    .pool()
+}
+
+/// Milliseconds since the Unix epoch, for `uuid7` and `ulid`.
+pub fn unix_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_millis() as u64)
+}
+
+/// A random version 4 UUID in canonical lowercase form.
+pub fn uuid4<R: RngCore + CryptoRng>(rng: &mut R) -> String {
+    let mut bytes = [0u8; 16];
+    rng.fill_bytes(&mut bytes);
+    format_uuid(bytes, 4)
+}
+
+/// A version 7 UUID for the Unix time `millis`.
+pub fn uuid7<R: RngCore + CryptoRng>(rng: &mut R, millis: u64) -> String {
+    let mut bytes = [0u8; 16];
+    rng.fill_bytes(&mut bytes[6..]);
+    bytes[..6].copy_from_slice(&millis.to_be_bytes()[2..]);
+    format_uuid(bytes, 7)
+}
+
+/// A ULID for the Unix time `millis`.
+pub fn ulid<R: RngCore + CryptoRng>(rng: &mut R, millis: u64) -> String {
+    let mut random = [0u8; 10];
+    rng.fill_bytes(&mut random);
+    let value = random
+        .iter()
+        .fold(u128::from(millis & 0xffff_ffff_ffff), |acc, &b| {
+            (acc << 8) | u128::from(b)
+        });
+    (0..26)
+        .map(|i| char::from(CROCKFORD[(value >> (5 * (25 - i))) as usize & 31]))
+        .collect()
+}
+
+/// Stamp the version and RFC 9562 variant into `bytes` and format them.
+fn format_uuid(mut bytes: [u8; 16], version: u8) -> String {
+    bytes[6] = (bytes[6] & 0x0f) | (version << 4);
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+    let hex: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate::seeded_rng;
+
+    #[test]
+    fn test_uuid_version_and_variant() {
+        let mut rng = seeded_rng("14").unwrap();
+        let v4 = uuid4(&mut rng);
+        assert_eq!(v4.len(), 36);
+        assert_eq!(&v4[14..15], "4");
+        assert!("89ab".contains(&v4[19..20]));
+
+        let v7 = uuid7(&mut rng, 0x0123_4567_89ab);
+        assert!(v7.starts_with("01234567-89ab-7"), "{}", v7);
+        assert!("89ab".contains(&v7[19..20]));
+    }
+
+    #[test]
+    fn test_ulid_encodes_timestamp() {
+        let mut rng = seeded_rng("14").unwrap();
+        let id = ulid(&mut rng, 1_469_918_176_385);
+        assert_eq!(id.len(), 26);
+        // timestamp example from the ULID specification
+        assert_eq!(&id[..10], "01ARYZ6S41");
+        assert!(id.bytes().all(|b| CROCKFORD.contains(&b)));
+    }
+
+    #[test]
+    fn test_nanoid_alphabet_applies_exclusions() {
+        assert_eq!(nanoid_alphabet(&CharsetSpec::default()).len(), 64);
+        let spec = CharsetSpec {
+            exclude: "_-".into(),
+            no_ambiguous: true,
+            ..CharsetSpec::default()
+        };
+        let alphabet = nanoid_alphabet(&spec);
+        assert_eq!(alphabet.len(), 64 - 2 - 5);
+        assert!(!alphabet.iter().any(|c| "_-1lI0O".contains(*c)));
+        let custom = CharsetSpec {
+            charset: Some("abc".into()),
+            exclude: "b".into(),
+            ..CharsetSpec::default()
+        };
+        assert_eq!(nanoid_alphabet(&custom), vec!['a', 'c']);
+    }
+}
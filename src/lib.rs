@@ -2,9 +2,19 @@
 //!
 //! This crate provides the core functionality for the `genix` CLI. It is
 //! organized into small modules: `generate` (password/passphrase generation),
-//! `clipboard` (cross-platform clipboard helper), and `entropy` (entropy
-//! estimation and helpers). The binary `src/main.rs` calls `genix_lib::run()` to
-//! execute the CLI.
+//! `charset` (character pool composition for the `random` style), `clipboard`
+//! (cross-platform clipboard helper), `entropy` (entropy estimation and
+//! helpers), `output` (JSON/CSV records and verdicts), `breach` (offline Pwned
+//! Passwords lookups), `matching` (zxcvbn-style pattern matching for guess
+//! estimates), `bip39` (BIP39 mnemonics), `identifier` (UUID, ULID and NanoID),
+//! `token` (prefixed checksummed API tokens), `totp` (base32 TOTP secrets,
+//! `otpauth://` URIs and codes), `diceware` (passphrases from physical dice
+//! rolls), `policy` (password policies from flags or TOML/JSON files, and
+//! compliance checks), `selftest` (statistical uniformity tests of every
+//! style), `pattern` (template mini-language for the `pattern` style),
+//! `passphrase` (passphrase formatting and parsing), `wordlist` (built-in EFF
+//! lists and wordlist files), and `error` (the shared `GenixError` type). The
+//! binary `src/main.rs` calls `genix_lib::run()` to execute the CLI.
 //!
 //! Public API
 //!
@@ -12,14 +22,42 @@
 //!
 //! See each module for detailed documentation on functions and behavior.
 
+pub mod bip39;
+pub mod breach;
+pub mod charset;
 pub mod clipboard;
+pub mod diceware;
 pub mod entropy;
+pub mod error;
 pub mod generate;
+pub mod identifier;
+pub mod matching;
+pub mod output;
+pub mod passphrase;
+pub mod pattern;
+pub mod policy;
+pub mod selftest;
+pub mod token;
+pub mod totp;
+pub mod wordlist;
 
-use clap::{ArgAction, Parser, Subcommand};
+use std::io::{self, BufRead, IsTerminal, Write};
 
+use clap::{ArgAction, Args, Parser, Subcommand};
+use rand::{CryptoRng, RngCore};
+
+use crate::breach::{BreachDb, build_index};
+use crate::charset::CharsetSpec;
 use crate::clipboard::copy_to_clipboard;
-use crate::generate::generate_many;
+use crate::diceware::DicewareList;
+use crate::entropy::{AttackRates, EntropyProfile, EstimateOptions, Estimator, display_duration};
+use crate::error::GenixError;
+use crate::generate::{Base64Encoding, Generator, GeneratorConfig, LengthUnit, Style, seeded_rng};
+use crate::output::{CheckRecord, GeneratedRecord, OutputFormat, VERDICTS, verdict};
+use crate::passphrase::{Capitalize, PassphraseFormat};
+use crate::policy::{ComplianceReport, Policy, PolicyChecker};
+use crate::selftest::{SelftestOptions, selftest, selftest_with_rng};
+use crate::wordlist::{WordlistSource, analyze, load_wordlist};
 
 /// Top-level CLI types and runner. Keep `main.rs` thin.
 #[derive(Parser, Debug)]
@@ -29,51 +67,345 @@ struct Cli {
     command: Commands,
 }
 
+// Parsed once per process, so the size of `Generate` does not matter.
+#[allow(clippy::large_enum_variant)]
 #[derive(Subcommand, Debug)]
 enum Commands {
     /// Generate passwords or passphrases
     Generate {
-        /// Length (characters or bytes depending on style)
-        #[arg(short = 'l', long = "length", default_value_t = 20usize)]
-        length: usize,
+        /// Length (characters, bytes or words depending on style and --length-unit; default 20,
+        /// 21 for nanoid, 30 for token)
+        #[arg(short = 'l', long = "length")]
+        length: Option<usize>,
+
+        /// Word count: passphrase (default 20) or bip39 (12, 15, 18, 21 or 24; default 12); overrides
+        /// --length
+        #[arg(long = "words", value_name = "N")]
+        words: Option<usize>,
 
         /// Number of items to generate
         #[arg(short = 'n', long = "count", default_value_t = 1usize)]
         count: usize,
 
-        /// Style: random, passphrase, pin, hex, base64
-        #[arg(long = "style", default_value = "random")]
-        style: String,
+        /// Style: random, passphrase, pin, hex, base64, base32, pattern, pronounceable, bip39,
+        /// uuid4, uuid7, ulid, nanoid, token
+        #[arg(long = "style", default_value_t = Style::Random)]
+        style: Style,
+
+        /// Template for the pattern style, e.g. "Cvccvc-9999-ss" or "XXXX-XXXX-XXXX"
+        #[arg(long = "pattern", value_name = "TEMPLATE", allow_hyphen_values = true)]
+        pattern: Option<String>,
+
+        /// Prefix for the token style, e.g. "svc_live"
+        #[arg(long = "prefix", default_value = token::DEFAULT_PREFIX)]
+        prefix: String,
+
+        /// Base64 alphabet and padding: standard, url, url-nopad, standard-nopad
+        #[arg(long = "encoding", default_value_t = Base64Encoding::Standard)]
+        encoding: Base64Encoding,
+
+        /// What --length counts for hex, base64 and base32: bytes (random input) or chars (output)
+        #[arg(long = "length-unit", default_value_t = LengthUnit::Bytes)]
+        length_unit: LengthUnit,
+
+        /// Omit the trailing '=' padding of base32 output
+        #[arg(long = "no-padding", action = ArgAction::SetTrue)]
+        no_padding: bool,
 
         /// Copy first result to clipboard
         #[arg(long = "clipboard", action = ArgAction::SetTrue)]
         clipboard: bool,
 
-        /// Use a custom wordlist file for passphrase style
-        #[arg(long = "wordlist")]
+        /// Passphrase wordlist: eff-large (default), eff-short1, eff-short2, or a file path
+        #[arg(long = "wordlist", value_name = "LIST")]
         wordlist: Option<String>,
 
+        /// Separator between passphrase words
+        #[arg(
+            long = "separator",
+            value_name = "SEP",
+            default_value = "-",
+            allow_hyphen_values = true
+        )]
+        separator: String,
+
+        /// Pick each passphrase separator at random (adds entropy)
+        #[arg(long = "random-separator", action = ArgAction::SetTrue)]
+        random_separator: bool,
+
+        /// Passphrase capitalization: none, first, all, random
+        #[arg(long = "capitalize", default_value_t = Capitalize::None)]
+        capitalize: Capitalize,
+
+        /// Append a random digit to a random passphrase word
+        #[arg(long = "add-digit", action = ArgAction::SetTrue)]
+        add_digit: bool,
+
+        /// Append a random symbol to a random passphrase word
+        #[arg(long = "add-symbol", action = ArgAction::SetTrue)]
+        add_symbol: bool,
+
+        /// Refuse wordlists with duplicates, non-ASCII words or too few words
+        #[arg(long = "strict", action = ArgAction::SetTrue)]
+        strict: bool,
+
         /// Avoid ambiguous characters (1,l,I,0,O,|)
         #[arg(long = "no-ambiguous", action = ArgAction::SetTrue)]
         no_ambiguous: bool,
 
+        /// Include lowercase letters (if any class flag is given, only those classes are used)
+        #[arg(long = "lower", action = ArgAction::SetTrue)]
+        lower: bool,
+
+        /// Include uppercase letters
+        #[arg(long = "upper", action = ArgAction::SetTrue)]
+        upper: bool,
+
+        /// Include digits
+        #[arg(long = "digits", action = ArgAction::SetTrue)]
+        digits: bool,
+
+        /// Include symbols
+        #[arg(long = "symbols", action = ArgAction::SetTrue)]
+        symbols: bool,
+
+        /// Draw from exactly these characters instead of the class flags (also the nanoid alphabet)
+        #[arg(
+            long = "charset",
+            value_name = "CHARS",
+            allow_hyphen_values = true,
+            conflicts_with_all = ["lower", "upper", "digits", "symbols", "symbols_set"]
+        )]
+        charset: Option<String>,
+
+        /// Remove these characters from the pool (or the nanoid alphabet)
+        #[arg(long = "exclude", value_name = "CHARS", allow_hyphen_values = true)]
+        exclude: Option<String>,
+
+        /// Replace the default symbol class with these characters
+        #[arg(long = "symbols-set", value_name = "CHARS", allow_hyphen_values = true)]
+        symbols_set: Option<String>,
+
+        /// Require at least N uppercase letters (random style)
+        #[arg(long = "require-upper", value_name = "N", default_value_t = 0usize)]
+        require_upper: usize,
+
+        /// Require at least N lowercase letters (random style)
+        #[arg(long = "require-lower", value_name = "N", default_value_t = 0usize)]
+        require_lower: usize,
+
+        /// Require at least N digits (random style)
+        #[arg(long = "require-digit", value_name = "N", default_value_t = 0usize)]
+        require_digit: usize,
+
+        /// Require at least N symbols (random style)
+        #[arg(long = "require-symbol", value_name = "N", default_value_t = 0usize)]
+        require_symbol: usize,
+
         /// Minimum entropy (bits). If provided, length may be auto-increased.
         #[arg(long = "min-entropy")]
         min_entropy: Option<f64>,
+
+        /// Policy file (TOML or JSON) the results must comply with; --require-* flags raise its minimums
+        #[arg(long = "policy", value_name = "FILE")]
+        policy: Option<String>,
+
+        /// Output format: text (one item per line), json or csv (with style, length, entropy, verdict)
+        #[arg(long = "format", default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
+
+        /// Regenerate any result found in this Pwned Passwords dump or index
+        #[arg(long = "reject-breached", value_name = "DB")]
+        reject_breached: Option<String>,
+
+        /// INSECURE, tests only: derive all output from this hex seed (ChaCha20)
+        #[arg(long = "seed", value_name = "HEX")]
+        seed: Option<String>,
     },
     /// Estimate strength of a single string
     Check {
-        /// Input string to check
-        input: String,
-        /// Optional style hint (random|passphrase|pin|hex|base64)
+        #[command(flatten)]
+        input: InputArgs,
+        /// Optional style hint (see `generate --style`)
         #[arg(long = "style")]
-        style: Option<String>,
+        style: Option<Style>,
+        /// Separator between passphrase words (default: inferred)
+        #[arg(long = "separator", value_name = "SEP", allow_hyphen_values = true)]
+        separator: Option<String>,
+        /// Wordlist the passphrase came from: eff-large (default), eff-short1, eff-short2, or a file path
+        #[arg(long = "wordlist", value_name = "LIST")]
+        wordlist: Option<String>,
+        /// Strength model: guesses (pattern matching) or naive (charset * length)
+        #[arg(long = "estimator", default_value_t = Estimator::Guesses)]
+        estimator: Estimator,
+        /// Look the input up in a Pwned Passwords dump (sorted HASH:COUNT) or `breach-db build` index
+        #[arg(long = "breach-db", value_name = "PATH")]
+        breach_db: Option<String>,
+        /// Check each rule of a TOML or JSON policy file; exits 1 if any rule fails
+        #[arg(long = "policy", value_name = "FILE")]
+        policy: Option<String>,
     },
     /// Profile a password (gives entropy estimate and breakdown)
     Profile {
-        input: String,
+        #[command(flatten)]
+        input: InputArgs,
         #[arg(long = "style")]
-        style: Option<String>,
+        style: Option<Style>,
+        /// Separator between passphrase words (default: inferred)
+        #[arg(long = "separator", value_name = "SEP", allow_hyphen_values = true)]
+        separator: Option<String>,
+        /// Wordlist the passphrase came from: eff-large (default), eff-short1, eff-short2, or a file path
+        #[arg(long = "wordlist", value_name = "LIST")]
+        wordlist: Option<String>,
+        /// Strength model: guesses (pattern matching) or naive (charset * length)
+        #[arg(long = "estimator", default_value_t = Estimator::Guesses)]
+        estimator: Estimator,
+        /// Guesses per second of a rate-limited online attack (default: 100 per hour)
+        #[arg(long = "online-throttled-rate", value_name = "RATE", value_parser = parse_rate)]
+        online_throttled_rate: Option<f64>,
+        /// Guesses per second of an online attack without rate limiting (default: 10)
+        #[arg(long = "online-rate", value_name = "RATE", value_parser = parse_rate)]
+        online_rate: Option<f64>,
+        /// Guesses per second against a slow hash such as bcrypt/argon2 (default: 1e4)
+        #[arg(long = "slow-hash-rate", value_name = "RATE", value_parser = parse_rate)]
+        slow_hash_rate: Option<f64>,
+        /// Guesses per second against a fast hash such as MD5/NTLM on GPUs (default: 1e10)
+        #[arg(long = "fast-hash-rate", value_name = "RATE", value_parser = parse_rate)]
+        fast_hash_rate: Option<f64>,
+    },
+    /// Build a passphrase from physical dice rolls (never uses the system RNG)
+    Diceware {
+        /// Roll groups such as 41526 23456; read from stdin (prompting on a terminal) if omitted
+        rolls: Vec<String>,
+        /// Number of words to read from stdin
+        #[arg(short = 'w', long = "words", default_value_t = 6usize)]
+        words: usize,
+        /// Numbered wordlist: eff-large (default), eff-short1, eff-short2, or a file path
+        #[arg(long = "wordlist", value_name = "LIST")]
+        wordlist: Option<String>,
+        /// Separator between words
+        #[arg(long = "separator", default_value = "-", allow_hyphen_values = true)]
+        separator: String,
+    },
+    /// Create a base32 TOTP secret and its otpauth:// URI
+    TotpSecret {
+        /// Service name shown in the authenticator app
+        #[arg(long = "issuer")]
+        issuer: String,
+        /// Account name shown in the authenticator app
+        #[arg(long = "account")]
+        account: String,
+        /// Secret size in bytes (at least 16)
+        #[arg(long = "bytes", default_value_t = totp::SECRET_BYTES)]
+        bytes: usize,
+        /// Code length (6 to 8)
+        #[arg(long = "digits", default_value_t = totp::DEFAULT_DIGITS)]
+        digits: u32,
+        /// Time step in seconds
+        #[arg(long = "period", default_value_t = totp::DEFAULT_PERIOD)]
+        period: u64,
+    },
+    /// Print the current TOTP code for a base32 secret
+    Totp {
+        /// Base32 secret (case, spaces and padding are ignored)
+        #[arg(long = "secret")]
+        secret: String,
+        /// Code length (6 to 8)
+        #[arg(long = "digits", default_value_t = totp::DEFAULT_DIGITS)]
+        digits: u32,
+        /// Time step in seconds
+        #[arg(long = "period", default_value_t = totp::DEFAULT_PERIOD)]
+        period: u64,
+    },
+    /// Run statistical uniformity tests on every style; exits non-zero on failure
+    Selftest {
+        /// Items generated per style (refused if too few for the frequency test)
+        #[arg(long = "samples", default_value_t = 10_000usize)]
+        samples: usize,
+        /// Family-wise significance level (Bonferroni-corrected across all tests)
+        #[arg(long = "alpha", default_value_t = 0.001)]
+        alpha: f64,
+        /// Only test this style (repeatable; default: all)
+        #[arg(long = "style")]
+        styles: Vec<Style>,
+        /// INSECURE, tests only: derive all samples from this hex seed (ChaCha20)
+        #[arg(long = "seed", value_name = "HEX")]
+        seed: Option<String>,
+    },
+    /// Inspect wordlists
+    Wordlist {
+        #[command(subcommand)]
+        command: WordlistCommand,
+    },
+    /// Prepare offline Pwned Passwords data
+    BreachDb {
+        #[command(subcommand)]
+        command: BreachDbCommand,
+    },
+}
+
+/// Where `check` and `profile` read candidates from.
+#[derive(Args, Debug)]
+struct InputArgs {
+    /// Candidate to check (ends up in shell history; omit it to be prompted, or use --stdin/--file)
+    #[arg(conflicts_with_all = ["stdin", "file"])]
+    input: Option<String>,
+    /// Read candidates from stdin, one per line
+    #[arg(long = "stdin", action = ArgAction::SetTrue, conflicts_with = "file")]
+    stdin: bool,
+    /// Read candidates from a file, one per line
+    #[arg(long = "file", value_name = "PATH")]
+    file: Option<String>,
+    /// Include each candidate in batch, JSON and CSV results and in a prompted profile (hidden by default)
+    #[arg(long = "show", action = ArgAction::SetTrue)]
+    show: bool,
+    /// Batch mode: print only a histogram of verdicts
+    #[arg(long = "summary", action = ArgAction::SetTrue)]
+    summary: bool,
+    /// Prompt: ask twice and fail if the entries differ
+    #[arg(long = "confirm", action = ArgAction::SetTrue)]
+    confirm: bool,
+    /// Output format: text, json or csv
+    #[arg(long = "format", default_value_t = OutputFormat::Text)]
+    format: OutputFormat,
+}
+
+impl InputArgs {
+    /// Whether candidates come from `--stdin` or `--file`.
+    fn is_batch(&self) -> bool {
+        self.stdin || self.file.is_some()
+    }
+
+    /// The positional input, or a secret typed at the hidden TTY prompt.
+    fn single(&self) -> String {
+        match &self.input {
+            Some(input) => input.clone(),
+            None => prompt_secret(self.confirm).unwrap_or_else(|e| fail(e)),
+        }
+    }
+}
+
+#[derive(Subcommand, Debug)]
+enum WordlistCommand {
+    /// Report size, duplicates, prefix violations and bits per word
+    Check {
+        /// eff-large, eff-short1, eff-short2, or a file path
+        #[arg(default_value = "eff-large")]
+        list: String,
+        /// Separator the list will be used with (prefix violations only matter when empty)
+        #[arg(long = "separator", default_value = "-", allow_hyphen_values = true)]
+        separator: String,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum BreachDbCommand {
+    /// Convert a sorted SHA-1 HASH:COUNT dump into a compact binary index
+    Build {
+        /// The ordered-by-hash text dump
+        input: String,
+        /// Where to write the index
+        output: String,
     },
 }
 
@@ -87,8 +419,20 @@ enum Commands {
 /// Behavior summary:
 /// - `generate` — produce one or more passwords/passphrases and optionally copy
 ///   the first result to the clipboard.
-/// - `check` — print an estimated entropy (bits) for a single input string.
-/// - `profile` — print a small profile (entropy and charset hint) for an input.
+/// - `check` — print an estimated entropy (bits) for a single input string,
+///   capped by the pattern-matching guess estimate unless `--estimator naive`;
+///   `--stdin`/`--file` check one candidate per line without echoing it, and
+///   with no input at all the candidate is read from a hidden TTY prompt.
+///   `--policy` checks each rule of a policy file and exits 1 on failure.
+/// - `profile` — print a small profile (entropy, charset hint and crack times
+///   per attacker model) for an input.
+/// - `diceware` — map physical dice rolls to a passphrase.
+/// - `totp-secret` — create a TOTP secret and `otpauth://` URI; `totp` —
+///   print the current code for a secret.
+/// - `selftest` — chi-squared, serial-correlation and runs tests per style.
+/// - `breach-db build` — index a Pwned Passwords dump for `check --breach-db`
+///   and `generate --reject-breached`.
+/// - `wordlist check` — report the quality of a built-in or custom wordlist.
 ///
 /// Example:
 ///
@@ -100,68 +444,255 @@ pub fn run() {
     match cli.command {
         Commands::Generate {
             length,
+            words,
             count,
             style,
+            pattern,
+            prefix,
+            encoding,
+            length_unit,
+            no_padding,
             clipboard,
             wordlist,
+            separator,
+            random_separator,
+            capitalize,
+            add_digit,
+            add_symbol,
+            strict,
             no_ambiguous,
+            lower,
+            upper,
+            digits,
+            symbols,
+            charset,
+            exclude,
+            symbols_set,
+            require_upper,
+            require_lower,
+            require_digit,
+            require_symbol,
             min_entropy,
+            policy,
+            format,
+            reject_breached,
+            seed,
         } => {
-            let results = generate_many(
-                &style,
-                length,
-                count,
-                wordlist.as_deref(),
+            let any_class = lower || upper || digits || symbols;
+            let spec = CharsetSpec {
+                lower: lower || !any_class,
+                upper: upper || !any_class,
+                digits: digits || !any_class,
+                symbols: symbols || !any_class,
+                symbols_set,
+                charset,
+                exclude: exclude.unwrap_or_default(),
                 no_ambiguous,
-                min_entropy,
-            )
-            .unwrap_or_else(|e| {
-                eprintln!("error: {}", e);
-                std::process::exit(1);
-            });
+            };
+            let mut policy = policy
+                .map(|path| Policy::from_file(&path).unwrap_or_else(|e| fail(e)))
+                .unwrap_or_default();
+            policy.min_upper = policy.min_upper.max(require_upper);
+            policy.min_lower = policy.min_lower.max(require_lower);
+            policy.min_digit = policy.min_digit.max(require_digit);
+            policy.min_symbol = policy.min_symbol.max(require_symbol);
+            let length = words.or(length).unwrap_or_else(|| style.default_length());
+            let mut config = GeneratorConfig::new(style)
+                .length(length)
+                .charset(spec)
+                .policy(policy)
+                .strict_wordlist(strict)
+                .token_prefix(prefix)
+                .padding(!no_padding)
+                .encoding(encoding)
+                .length_unit(length_unit)
+                .passphrase_format(PassphraseFormat {
+                    separator,
+                    random_separator,
+                    capitalize,
+                    add_digit,
+                    add_symbol,
+                });
+            if let Some(template) = pattern {
+                config = config.pattern(template);
+            }
+            if let Some(path) = wordlist {
+                config = config.wordlist(path);
+            }
+            if let Some(bits) = min_entropy {
+                config = config.min_entropy(bits);
+            }
+            let mut breach_db =
+                reject_breached.map(|path| BreachDb::open(&path).unwrap_or_else(|e| fail(e)));
+            let results = match seed {
+                Some(hex) => {
+                    eprintln!(
+                        "warning: --seed output is predictable by anyone who knows the seed; never use it for real secrets"
+                    );
+                    let rng = seeded_rng(&hex).unwrap_or_else(|e| fail(e));
+                    take_results(config.build_with_rng(rng), count, breach_db.as_mut())
+                }
+                None => take_results(config.build(), count, breach_db.as_mut()),
+            };
 
-            for line in &results {
-                println!("{}", line);
+            match format {
+                OutputFormat::Text => {
+                    for record in &results {
+                        println!("{}", record.value);
+                    }
+                }
+                OutputFormat::Json => println!("{}", to_json(&results)),
+                OutputFormat::Csv => {
+                    println!("{}", GeneratedRecord::CSV_HEADER);
+                    for record in &results {
+                        println!("{}", record.csv_row());
+                    }
+                }
             }
 
             if clipboard
                 && !results.is_empty()
-                && let Err(e) = copy_to_clipboard(&results[0])
+                && let Err(e) = copy_to_clipboard(&results[0].value)
             {
                 eprintln!("warning: failed to copy to clipboard: {}", e);
             }
         }
-        Commands::Check { input, style } => {
-            let s = input;
-            let st = style.as_deref().unwrap_or("random");
-            match crate::entropy::estimate_entropy_detailed(&s, st) {
-                Ok(profile) => {
+        Commands::Check {
+            input,
+            style,
+            separator,
+            wordlist,
+            estimator,
+            breach_db,
+            policy,
+        } => {
+            let mut assessor = Assessor {
+                style: style.unwrap_or(Style::Random),
+                options: EstimateOptions {
+                    separator,
+                    wordlist: wordlist.map(WordlistSource::from),
+                    estimator,
+                    ..EstimateOptions::default()
+                },
+                breach_db: breach_db.map(|path| BreachDb::open(&path).unwrap_or_else(|e| fail(e))),
+                policy: policy.map(|path| {
+                    Policy::from_file(&path)
+                        .and_then(|policy| policy.checker())
+                        .unwrap_or_else(|e| fail(e))
+                }),
+                detailed: false,
+            };
+            if input.is_batch() {
+                check_batch(&input, &mut assessor);
+                return;
+            }
+            let s = input.single();
+            if input.format != OutputFormat::Text {
+                print_single_record(&input, &s, &mut assessor);
+                return;
+            }
+            match assessor.assess(&s) {
+                Ok(assessment) => {
+                    let profile = &assessment.profile;
                     println!("Estimated entropy: {:.2} bits", profile.bits);
-                    let verdict = match profile.bits {
-                        b if b < 40.0 => "very weak",
-                        b if b < 64.0 => "weak",
-                        b if b < 80.0 => "fair",
-                        b if b < 128.0 => "strong",
-                        _ => "very strong",
-                    };
-                    println!("Verdict: {}", verdict);
+                    if let Some(guesses) = &profile.guesses {
+                        println!(
+                            "Guesses: {:.2e} (log2 {:.2})",
+                            guesses.guesses(),
+                            guesses.guesses_log2
+                        );
+                    }
+                    if assessor.breach_db.is_some() {
+                        match assessment.breach_count {
+                            Some(count) => println!("Breached: yes, seen {} times", count),
+                            None => println!("Breached: not found"),
+                        }
+                    }
+                    println!("Verdict: {}", assessment.verdict);
+                    if let Some(report) = &assessment.compliance {
+                        print_compliance(report);
+                        if !report.passed() {
+                            std::process::exit(1);
+                        }
+                    }
                 }
-                Err(e) => eprintln!("error estimating entropy: {}", e),
+                Err(e) => fail(e),
             }
         }
-        Commands::Profile { input, style } => {
-            let st = style.as_deref().unwrap_or("random");
-            println!("Profile for: {} (style: {})", input, st);
-            match crate::entropy::estimate_entropy_detailed(&input, st) {
+        Commands::Profile {
+            input,
+            style,
+            separator,
+            wordlist,
+            estimator,
+            online_throttled_rate,
+            online_rate,
+            slow_hash_rate,
+            fast_hash_rate,
+        } => {
+            let st = style.unwrap_or(Style::Random);
+            let defaults = AttackRates::DEFAULT;
+            let options = EstimateOptions {
+                separator,
+                wordlist: wordlist.map(WordlistSource::from),
+                estimator,
+                rates: AttackRates {
+                    online_throttled: online_throttled_rate.unwrap_or(defaults.online_throttled),
+                    online_unthrottled: online_rate.unwrap_or(defaults.online_unthrottled),
+                    offline_slow: slow_hash_rate.unwrap_or(defaults.offline_slow),
+                    offline_fast: fast_hash_rate.unwrap_or(defaults.offline_fast),
+                },
+            };
+            if input.is_batch() || input.format != OutputFormat::Text {
+                let mut assessor = Assessor {
+                    style: st,
+                    options,
+                    breach_db: None,
+                    policy: None,
+                    detailed: true,
+                };
+                if input.is_batch() {
+                    check_batch(&input, &mut assessor);
+                } else {
+                    print_single_record(&input, &input.single(), &mut assessor);
+                }
+                return;
+            }
+            let candidate = input.single();
+            // a prompted candidate stays hidden unless asked for
+            if input.input.is_some() || input.show {
+                println!("Profile for: {} (style: {})", candidate, st);
+            } else {
+                println!("Profile (style: {})", st);
+            }
+            let input = candidate;
+            match crate::entropy::estimate_entropy_detailed_with(&input, st, &options) {
                 Ok(profile) => {
                     println!("Entropy: {:.2} bits", profile.bits);
                     if let Some(wc) = profile.word_count {
+                        let list = match st {
+                            Style::Bip39 => "bip39".to_string(),
+                            _ => options.wordlist.clone().unwrap_or_default().to_string(),
+                        };
                         println!(
-                            "Passphrase words: {} (assumed wordlist size: {})",
+                            "Passphrase words: {} (wordlist {}: {} words)",
                             wc,
+                            list,
                             profile.assumed_wordlist_size.unwrap_or(0)
                         );
-                        println!("Bits per word (assumed): {:.2}", profile.per_char);
+                        println!("Bits per word: {:.2}", profile.per_char);
+                        if !profile.unlisted_words.is_empty() {
+                            println!(
+                                "Not in wordlist (scored per character): {}",
+                                profile.unlisted_words.join(", ")
+                            );
+                        }
+                        if let Some(format_bits) = profile.format_bits {
+                            println!(
+                                "Formatting (case, separators, extras): {:.2} bits",
+                                format_bits
+                            );
+                        }
                     } else {
                         println!("Length: {} chars", profile.length);
                         println!("Charset size (inferred): {} symbols", profile.charset_size);
@@ -174,17 +705,561 @@ pub fn run() {
                             profile.has_symbol
                         );
                     }
-                    let verdict = match profile.bits {
-                        b if b < 40.0 => "very weak",
-                        b if b < 64.0 => "weak",
-                        b if b < 80.0 => "fair",
-                        b if b < 128.0 => "strong",
-                        _ => "very strong",
-                    };
-                    println!("Verdict: {}", verdict);
+                    if let Some(guesses) = &profile.guesses {
+                        println!(
+                            "Guesses: {:.2e} (log2 {:.2})",
+                            guesses.guesses(),
+                            guesses.guesses_log2
+                        );
+                        for m in &guesses.sequence {
+                            println!("  {}", m);
+                        }
+                    }
+                    println!("Time to crack:");
+                    for (scenario, seconds) in profile.crack_times.scenarios() {
+                        println!("  {:<20} {}", scenario, display_duration(seconds));
+                    }
+                    println!("Verdict: {}", verdict(profile.bits));
+                }
+                Err(e) => fail(e),
+            }
+        }
+        Commands::Diceware {
+            rolls,
+            words,
+            wordlist,
+            separator,
+        } => {
+            let source = wordlist.map(WordlistSource::from);
+            let list = DicewareList::load(source.as_ref()).unwrap_or_else(|e| fail(e));
+            let groups = if rolls.is_empty() {
+                read_rolls(&list, words)
+            } else {
+                list.split_rolls(&rolls.join(" "))
+                    .unwrap_or_else(|e| fail(e))
+            };
+            let passphrase = list
+                .passphrase(&groups, &separator)
+                .unwrap_or_else(|e| fail(e));
+            println!("{}", passphrase);
+            println!(
+                "Entropy: {:.2} bits ({} words x {:.2} bits, exact)",
+                groups.len() as f64 * list.bits_per_word(),
+                groups.len(),
+                list.bits_per_word()
+            );
+        }
+        Commands::TotpSecret {
+            issuer,
+            account,
+            bytes,
+            digits,
+            period,
+        } => {
+            totp::validate_secret_bytes(bytes).unwrap_or_else(|e| fail(e));
+            let mut generator = GeneratorConfig::new(Style::Base32)
+                .length(bytes)
+                .padding(false)
+                .build()
+                .unwrap_or_else(|e| fail(e));
+            let secret = generator.generate_one().unwrap_or_else(|e| fail(e));
+            let uri = totp::otpauth_uri(&secret, &issuer, &account, digits, period)
+                .unwrap_or_else(|e| fail(e));
+            println!("Secret: {}", secret);
+            println!("URI: {}", uri);
+        }
+        Commands::Totp {
+            secret,
+            digits,
+            period,
+        } => {
+            let key = totp::decode_secret(&secret).unwrap_or_else(|e| fail(e));
+            let now = identifier::unix_millis() / 1000;
+            let code = totp::totp(&key, now, period, digits).unwrap_or_else(|e| fail(e));
+            println!("{}", code);
+            eprintln!(
+                "info: valid for another {}s",
+                totp::seconds_remaining(now, period)
+            );
+        }
+        Commands::Selftest {
+            samples,
+            alpha,
+            styles,
+            seed,
+        } => {
+            let options = SelftestOptions {
+                samples,
+                alpha,
+                styles,
+            };
+            let report = match seed {
+                Some(hex) => {
+                    let mut rng = seeded_rng(&hex).unwrap_or_else(|e| fail(e));
+                    selftest_with_rng(&options, &mut rng)
+                }
+                None => selftest(&options),
+            }
+            .unwrap_or_else(|e| fail(e));
+            for stream in &report.streams {
+                let tests: Vec<String> = stream
+                    .tests
+                    .iter()
+                    .map(|t| format!("{} p={:.4}", t.name, t.p_value))
+                    .collect();
+                let status = if report.stream_failed(stream) {
+                    "FAIL"
+                } else {
+                    "ok"
+                };
+                println!(
+                    "{} {} ({} symbols, {} obs{}): {} -> {}",
+                    stream.style,
+                    stream.label,
+                    stream.alphabet,
+                    stream.observations,
+                    if stream.unexpected > 0 {
+                        format!(", {} unexpected", stream.unexpected)
+                    } else {
+                        String::new()
+                    },
+                    tests.join(", "),
+                    status
+                );
+            }
+            if report.passed() {
+                println!(
+                    "Selftest: pass (significance threshold p < {:.2e})",
+                    report.threshold
+                );
+            } else {
+                println!(
+                    "Selftest: FAIL (significance threshold p < {:.2e})",
+                    report.threshold
+                );
+                std::process::exit(1);
+            }
+        }
+        Commands::Wordlist {
+            command: WordlistCommand::Check { list, separator },
+        } => {
+            let source = WordlistSource::from(list);
+            let words = load_wordlist(Some(&source)).unwrap_or_else(|e| fail(e));
+            let report = analyze(&words);
+            println!("Wordlist: {}", source);
+            println!("Words: {} ({} unique)", report.total, report.unique);
+            println!("Bits per word: {:.2}", report.bits_per_word);
+            println!(
+                "Word length: min {}, avg {:.2}, max {}",
+                report.min_len, report.avg_len, report.max_len
+            );
+            println!("Characters: {}", report.charset);
+            if !report.duplicates.is_empty() {
+                println!("Duplicates: {}", report.duplicates.join(", "));
+            }
+            if !report.prefix_violations.is_empty() {
+                let examples: Vec<String> = report
+                    .prefix_violations
+                    .iter()
+                    .take(5)
+                    .map(|(prefix, word)| format!("{} < {}", prefix, word))
+                    .collect();
+                println!(
+                    "Prefix violations: {} (e.g. {})",
+                    report.prefix_violations.len(),
+                    examples.join(", ")
+                );
+            }
+            if !report.non_ascii.is_empty() {
+                println!("Non-ASCII words: {}", report.non_ascii.join(", "));
+            }
+            let problems = report.problems(&separator);
+            if problems.is_empty() {
+                println!("Strict: pass");
+            } else {
+                println!("Strict: fail ({})", problems.join("; "));
+            }
+        }
+        Commands::BreachDb {
+            command: BreachDbCommand::Build { input, output },
+        } => {
+            let reader = std::fs::File::open(&input)
+                .map(io::BufReader::new)
+                .map_err(|source| GenixError::BreachDbIo {
+                    path: input.clone(),
+                    source,
+                })
+                .unwrap_or_else(|e| fail(e));
+            let writer = std::fs::File::create(&output)
+                .map(io::BufWriter::new)
+                .map_err(|source| GenixError::BreachDbIo {
+                    path: output.clone(),
+                    source,
+                })
+                .unwrap_or_else(|e| fail(e));
+            let records = build_index(reader, writer).unwrap_or_else(|e| fail(e));
+            println!("Indexed {} hashes into {}", records, output);
+        }
+    }
+}
+
+/// Read `words` roll groups from stdin, prompting and re-asking on a terminal.
+fn read_rolls(list: &DicewareList, words: usize) -> Vec<String> {
+    let stdin = io::stdin();
+    let interactive = stdin.is_terminal();
+    let mut groups: Vec<String> = Vec::with_capacity(words);
+    let mut lines = stdin.lock().lines();
+    while groups.len() < words {
+        if interactive {
+            eprint!(
+                "Word {}/{}: roll {} dice: ",
+                groups.len() + 1,
+                words,
+                list.dice()
+            );
+            let _ = io::stderr().flush();
+        }
+        let line = match lines.next() {
+            Some(Ok(line)) => line,
+            _ => fail(GenixError::InvalidDiceware(format!(
+                "expected {} roll groups, got {}",
+                words,
+                groups.len()
+            ))),
+        };
+        match list.split_rolls(&line) {
+            Ok(new) => groups.extend(new),
+            Err(e) if interactive => eprintln!("error: {}", e),
+            Err(e) => fail(e),
+        }
+    }
+    groups.truncate(words);
+    groups
+}
+
+/// Collect `count` items from a freshly built generator, exiting on errors.
+/// Items found in `breach_db` are skipped and reported on stderr.
+fn take_results<R: RngCore + CryptoRng>(
+    generator: Result<Generator<R>, GenixError>,
+    count: usize,
+    breach_db: Option<&mut BreachDb>,
+) -> Vec<GeneratedRecord> {
+    let mut generator = generator.unwrap_or_else(|e| fail(e));
+    let entropy_bits = generator.entropy_bits();
+    let length = generator.length();
+    let style = generator.style();
+    let (results, rejected) = match breach_db {
+        Some(db) => db.take_unbreached(generator.generate_iter(), count),
+        None => generator
+            .generate_iter()
+            .take(count)
+            .collect::<Result<Vec<_>, _>>()
+            .map(|results| (results, 0)),
+    }
+    .unwrap_or_else(|e| fail(e));
+    if rejected > 0 {
+        eprintln!("info: regenerated {} breached result(s)", rejected);
+    }
+    results
+        .into_iter()
+        .map(|value| GeneratedRecord {
+            value,
+            style: style.to_string(),
+            length,
+            entropy_bits,
+            verdict: verdict(entropy_bits),
+        })
+        .collect()
+}
+
+/// Read a secret from the terminal with echo disabled, twice if `confirm`.
+fn prompt_secret(confirm: bool) -> Result<String, GenixError> {
+    let secret = rpassword::prompt_password("Password: ").map_err(GenixError::NoTerminal)?;
+    if confirm {
+        let again = rpassword::prompt_password("Confirm: ").map_err(GenixError::NoTerminal)?;
+        if again != secret {
+            return Err(GenixError::ConfirmMismatch);
+        }
+    }
+    Ok(secret)
+}
+
+/// Everything `check` and `profile` apply to each candidate.
+struct Assessor {
+    style: Style,
+    options: EstimateOptions,
+    breach_db: Option<BreachDb>,
+    policy: Option<PolicyChecker>,
+    /// `profile`: include crack times and the full profile in results
+    detailed: bool,
+}
+
+/// The results for one candidate.
+struct Assessment {
+    profile: EntropyProfile,
+    breach_count: Option<u64>,
+    /// `compromised` when the candidate was found in the breach database
+    verdict: &'static str,
+    compliance: Option<ComplianceReport>,
+}
+
+impl Assessor {
+    /// Estimate `candidate`, look it up in the breach database and check it
+    /// against the policy.
+    fn assess(&mut self, candidate: &str) -> Result<Assessment, GenixError> {
+        let profile =
+            crate::entropy::estimate_entropy_detailed_with(candidate, self.style, &self.options)?;
+        let breach_count = match &mut self.breach_db {
+            Some(db) => db.lookup(candidate)?,
+            None => None,
+        };
+        let verdict = if breach_count.is_some() {
+            "compromised"
+        } else {
+            verdict(profile.bits)
+        };
+        let compliance = self
+            .policy
+            .as_ref()
+            .map(|policy| policy.check(candidate, self.style, &self.options));
+        Ok(Assessment {
+            profile,
+            breach_count,
+            verdict,
+            compliance,
+        })
+    }
+
+    /// The JSON/CSV record for an assessment.
+    fn record<'a>(
+        &self,
+        assessment: &'a Assessment,
+        line: Option<usize>,
+        value: Option<&'a str>,
+    ) -> CheckRecord<'a> {
+        CheckRecord {
+            line,
+            value,
+            style: self.style.to_string(),
+            length: assessment.profile.length,
+            entropy_bits: assessment.profile.bits,
+            verdict: assessment.verdict,
+            breach_count: assessment.breach_count,
+            profile: self.detailed.then_some(&assessment.profile),
+            policy: assessment.compliance.as_ref(),
+        }
+    }
+
+    fn csv_header(&self) -> String {
+        CheckRecord::csv_header(self.detailed, self.policy.is_some())
+    }
+}
+
+/// Print one candidate as a JSON object or a CSV header and row. Exits with
+/// status 1 if it fails the policy.
+fn print_single_record(input: &InputArgs, candidate: &str, assessor: &mut Assessor) {
+    let assessment = assessor.assess(candidate).unwrap_or_else(|e| fail(e));
+    let record = assessor.record(&assessment, None, input.show.then_some(candidate));
+    if input.format == OutputFormat::Csv {
+        println!("{}", assessor.csv_header());
+        println!("{}", record.csv_row());
+    } else {
+        println!("{}", to_json(&record));
+    }
+    if assessment.compliance.is_some_and(|report| !report.passed()) {
+        std::process::exit(1);
+    }
+}
+
+/// Print the per-rule lines of a policy check.
+fn print_compliance(report: &ComplianceReport) {
+    let result = if report.passed() { "pass" } else { "FAIL" };
+    println!("Policy: {}", result);
+    for outcome in &report.rules {
+        let status = if outcome.passed { "pass" } else { "FAIL" };
+        println!("  {:<4}  {}: {}", status, outcome.rule, outcome.detail);
+    }
+}
+
+/// Estimate every line of `--stdin` or `--file` and print one result per
+/// line (text or CSV rows, or one JSON array), or only a verdict histogram
+/// with `--summary`. Candidates are never printed unless `--show` is given.
+/// Exits with status 1 if any candidate fails the policy.
+fn check_batch(batch: &InputArgs, assessor: &mut Assessor) {
+    let (name, reader): (String, Box<dyn BufRead>) = match &batch.file {
+        Some(path) => {
+            let file = std::fs::File::open(path)
+                .map_err(|source| GenixError::InputIo {
+                    path: path.clone(),
+                    source,
+                })
+                .unwrap_or_else(|e| fail(e));
+            (path.clone(), Box::new(io::BufReader::new(file)))
+        }
+        None => ("stdin".to_string(), Box::new(io::stdin().lock())),
+    };
+    let format = batch.format;
+    let mut tally = [0usize; VERDICTS.len()];
+    let mut errors = 0usize;
+    let mut policy_failures = 0usize;
+    let mut json_records = Vec::new();
+    if format == OutputFormat::Csv && !batch.summary {
+        println!("{}", assessor.csv_header());
+    }
+    for (index, line) in reader.split(b'\n').enumerate() {
+        let number = index + 1;
+        let line = line
+            .map_err(|source| GenixError::InputIo {
+                path: name.clone(),
+                source,
+            })
+            .unwrap_or_else(|e| fail(e));
+        let line = line.strip_suffix(b"\r").unwrap_or(&line);
+        if line.is_empty() {
+            continue;
+        }
+        let result = std::str::from_utf8(line)
+            .map_err(|_| "not valid UTF-8".to_string())
+            .and_then(|candidate| {
+                assessor
+                    .assess(candidate)
+                    .map(|assessment| (candidate, assessment))
+                    .map_err(|e| e.to_string())
+            });
+        let (candidate, assessment) = match result {
+            Ok(ok) => ok,
+            Err(e) => {
+                errors += 1;
+                match (batch.summary, format) {
+                    (true, _) => {}
+                    (false, OutputFormat::Text) => println!("line {}: error: {}", number, e),
+                    (false, _) => eprintln!("line {}: error: {}", number, e),
+                }
+                continue;
+            }
+        };
+        if let Some(slot) = VERDICTS.iter().position(|v| *v == assessment.verdict) {
+            tally[slot] += 1;
+        }
+        let compliant = assessment
+            .compliance
+            .as_ref()
+            .is_none_or(ComplianceReport::passed);
+        policy_failures += usize::from(!compliant);
+        if batch.summary {
+            continue;
+        }
+        let profile = &assessment.profile;
+        match format {
+            OutputFormat::Json => {
+                let record =
+                    assessor.record(&assessment, Some(number), batch.show.then_some(candidate));
+                json_records.push(serde_json::to_value(&record).expect("records serialize to JSON"))
+            }
+            OutputFormat::Csv => {
+                let record =
+                    assessor.record(&assessment, Some(number), batch.show.then_some(candidate));
+                println!("{}", record.csv_row())
+            }
+            OutputFormat::Text => {
+                let mut result = format!(
+                    "line {}: {:.2} bits, {}",
+                    number, profile.bits, assessment.verdict
+                );
+                if let Some(count) = assessment.breach_count {
+                    result.push_str(&format!(" (seen {} times)", count));
+                }
+                if assessor.detailed {
+                    result.push_str(&format!(
+                        ", slow hash: {}, fast hash: {}",
+                        display_duration(profile.crack_times.offline_slow),
+                        display_duration(profile.crack_times.offline_fast)
+                    ));
+                }
+                if let Some(report) = &assessment.compliance {
+                    if report.passed() {
+                        result.push_str(", policy: pass");
+                    } else {
+                        result.push_str(&format!(
+                            ", policy: fail ({})",
+                            report.failed_rules().join(", ")
+                        ));
+                    }
+                }
+                if batch.show {
+                    result.push_str(&format!(": {:?}", candidate));
                 }
-                Err(e) => eprintln!("error estimating entropy: {}", e),
+                println!("{}", result);
             }
         }
     }
+    let checked: usize = tally.iter().sum();
+    let with_policy = assessor.policy.is_some();
+    match (batch.summary, format) {
+        (false, OutputFormat::Json) => println!("{}", to_json(&json_records)),
+        (false, _) => {}
+        (true, OutputFormat::Json) => {
+            let verdicts: Vec<_> = VERDICTS
+                .iter()
+                .zip(tally)
+                .map(|(verdict, count)| serde_json::json!({ "verdict": verdict, "count": count }))
+                .collect();
+            let mut summary = serde_json::json!({
+                "checked": checked,
+                "errors": errors,
+                "verdicts": verdicts,
+            });
+            if with_policy {
+                summary["policy_failures"] = policy_failures.into();
+            }
+            println!("{}", to_json(&summary));
+        }
+        (true, OutputFormat::Csv) => {
+            println!("verdict,count");
+            for (verdict, count) in VERDICTS.iter().zip(tally) {
+                println!("{},{}", verdict, count);
+            }
+            println!("errors,{}", errors);
+            if with_policy {
+                println!("policy failures,{}", policy_failures);
+            }
+        }
+        (true, OutputFormat::Text) => {
+            println!("Checked: {} ({} errors)", checked, errors);
+            let widest = tally.iter().copied().max().unwrap_or(0).max(1);
+            for (verdict, count) in VERDICTS.iter().zip(tally) {
+                let bar = (count * 40).div_ceil(widest);
+                let row = format!("  {:<12} {:>7} {}", verdict, count, "#".repeat(bar));
+                println!("{}", row.trim_end());
+            }
+            if with_policy {
+                println!("Policy failures: {}", policy_failures);
+            }
+        }
+    }
+    if policy_failures > 0 {
+        std::process::exit(1);
+    }
+}
+
+/// Pretty-printed JSON for CLI output.
+fn to_json<T: serde::Serialize + ?Sized>(value: &T) -> String {
+    serde_json::to_string_pretty(value).expect("records serialize to JSON")
+}
+
+/// Parse a positive, finite guesses-per-second rate.
+fn parse_rate(s: &str) -> Result<f64, String> {
+    match s.parse::<f64>() {
+        Ok(rate) if rate.is_finite() && rate > 0.0 => Ok(rate),
+        _ => Err(format!(
+            "expected a positive number of guesses per second, got {:?}",
+            s
+        )),
+    }
+}
+
+/// Print `e` to stderr and exit with status 1.
+fn fail(e: GenixError) -> ! {
+    eprintln!("error: {}", e);
+    std::process::exit(1);
 }
@@ -0,0 +1,990 @@
+//! Pattern matching behind the `guesses` strength estimator.
+//!
+//! Modelled on zxcvbn: every substring of the password is matched against
+//! patterns an attacker would try first, each match is scored in guesses, and
+//! a dynamic program picks the sequence of non-overlapping matches (with
+//! brute force filling the gaps) that minimizes the total guesses. The
+//! patterns are:
+//!
+//! | Pattern      | Example        | Guesses                                   |
+//! |--------------|----------------|-------------------------------------------|
+//! | `dictionary` | `Passw0rd`     | rank, times case, l33t and reversal variations |
+//! | `spatial`    | `qwerty`       | QWERTY walks by length, turns and shifts  |
+//! | `repeat`     | `abcabc`       | guesses of the base times the repeat count |
+//! | `sequence`   | `1234`, `fedc` | start character times length              |
+//! | `date`       | `1987-06-15`   | years from now times 365                  |
+//! | `year`       | `1987`         | years from now                            |
+//! | `bruteforce` | `x7Q`          | charset size ^ length                     |
+//!
+//! Dictionaries are the common passwords in
+//! `assets/wordlists/common_passwords.txt` (ranked by popularity), the BIP39
+//! English words and, when vendored, the EFF large list (unranked, so every
+//! word costs the list size).
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::OnceLock;
+
+use serde::Serialize;
+
+use crate::bip39;
+use crate::charset::CharClass;
+use crate::entropy::class_charset_size;
+use crate::identifier::unix_millis;
+use crate::wordlist::BuiltinWordlist;
+
+const COMMON_PASSWORDS: &str = include_str!("../assets/wordlists/common_passwords.txt");
+
+/// Longer passwords are matched in chunks of this many characters.
+pub const MAX_ANALYZED_CHARS: usize = 100;
+
+/// zxcvbn's penalty base for each additional match in a sequence.
+const MIN_GUESSES_BEFORE_GROWING_SEQUENCE: f64 = 10_000.0;
+/// Lower bounds on the guesses of a non-brute-force match that does not
+/// cover the whole password.
+const MIN_SINGLE_CHAR_GUESSES: f64 = 10.0;
+const MIN_MULTI_CHAR_GUESSES: f64 = 50.0;
+/// Smallest year distance assumed for dates and years.
+const MIN_YEAR_SPACE: f64 = 20.0;
+
+const L33T_TABLE: [(char, &str); 12] = [
+    ('a', "4@"),
+    ('b', "8"),
+    ('c', "({[<"),
+    ('e', "3"),
+    ('g', "69"),
+    ('i', "1!|"),
+    ('l', "1|7"),
+    ('o', "0"),
+    ('s', "$5"),
+    ('t', "+7"),
+    ('x', "%"),
+    ('z', "2"),
+];
+/// At most this many l33t substitution combinations are tried.
+const MAX_L33T_COMBINATIONS: usize = 64;
+
+/// QWERTY rows (unshifted, shifted); rows after the first start one key to
+/// the right, so row `r + 1`, column `c` sits below and between row `r`
+/// columns `c - 1` and `c`.
+const QWERTY: [(&str, &str); 4] = [
+    ("`1234567890-=", "~!@#$%^&*()_+"),
+    (" qwertyuiop[]\\", " QWERTYUIOP{}|"),
+    (" asdfghjkl;'", " ASDFGHJKL:\""),
+    (" zxcvbnm,./", " ZXCVBNM<>?"),
+];
+
+const DATE_SEPARATORS: &str = " /\\_.-";
+
+/// The kind of a `Match`, with the details that determined its guesses.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "pattern", rename_all = "lowercase")]
+pub enum MatchKind {
+    /// A dictionary word, possibly with l33t substitutions or reversed.
+    Dictionary {
+        word: String,
+        rank: usize,
+        l33t: bool,
+        reversed: bool,
+    },
+    /// A walk over adjacent QWERTY keys.
+    Spatial { turns: usize, shifted: usize },
+    /// A base string repeated `count` times.
+    Repeat { base: String, count: usize },
+    /// Characters with a constant code point step (`abc`, `9753`).
+    Sequence { ascending: bool },
+    /// A day, month and year, with or without separators.
+    Date { year: i32, separator: bool },
+    /// A four-digit year.
+    Year { year: i32 },
+    /// Characters that matched no pattern.
+    Bruteforce { cardinality: usize },
+}
+
+impl MatchKind {
+    /// Short name of the pattern, as shown by `profile`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            MatchKind::Dictionary { .. } => "dictionary",
+            MatchKind::Spatial { .. } => "spatial",
+            MatchKind::Repeat { .. } => "repeat",
+            MatchKind::Sequence { .. } => "sequence",
+            MatchKind::Date { .. } => "date",
+            MatchKind::Year { .. } => "year",
+            MatchKind::Bruteforce { .. } => "bruteforce",
+        }
+    }
+}
+
+/// A scored pattern covering the characters `start..=end` of the password.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Match {
+    pub kind: MatchKind,
+    /// Index of the first character (in chars, not bytes).
+    pub start: usize,
+    /// Index of the last character, inclusive.
+    pub end: usize,
+    pub token: String,
+    /// `log2` of the guesses needed to find this part on its own.
+    pub guesses_log2: f64,
+}
+
+impl fmt::Display for Match {
+    /// `token  pattern (details)  log2 guesses`, one line of `profile` output.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let details = match &self.kind {
+            MatchKind::Dictionary {
+                word,
+                rank,
+                l33t,
+                reversed,
+            } => {
+                let mut details = format!("{:?}, rank {}", word, rank);
+                if *l33t {
+                    details.push_str(", l33t");
+                }
+                if *reversed {
+                    details.push_str(", reversed");
+                }
+                details
+            }
+            MatchKind::Spatial { turns, shifted } => {
+                format!("{} turns, {} shifted", turns, shifted)
+            }
+            MatchKind::Repeat { base, count } => format!("{:?} x{}", base, count),
+            MatchKind::Sequence { ascending } => if *ascending {
+                "ascending"
+            } else {
+                "descending"
+            }
+            .to_string(),
+            MatchKind::Date { year, separator } => {
+                let separator = if *separator { "with" } else { "without" };
+                format!("year {}, {} separators", year, separator)
+            }
+            MatchKind::Year { year } => year.to_string(),
+            MatchKind::Bruteforce { cardinality } => format!("charset {}", cardinality),
+        };
+        write!(
+            f,
+            "{:?} {} ({}): {:.2} bits",
+            self.token,
+            self.kind.name(),
+            details,
+            self.guesses_log2
+        )
+    }
+}
+
+/// Result of `estimate_guesses`: total guesses and the cheapest decomposition.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GuessEstimate {
+    /// `log2` of the estimated number of guesses; comparable to bits of entropy.
+    pub guesses_log2: f64,
+    /// Non-overlapping matches covering the password, in order.
+    pub sequence: Vec<Match>,
+}
+
+impl GuessEstimate {
+    /// The estimated number of guesses (may be `inf` for very long inputs).
+    pub fn guesses(&self) -> f64 {
+        self.guesses_log2.exp2()
+    }
+}
+
+/// The bundled common passwords, most popular first.
+pub fn common_passwords() -> impl Iterator<Item = &'static str> {
+    COMMON_PASSWORDS
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+}
+
+/// Estimate how many guesses a pattern-aware attacker needs for `password`.
+///
+/// Passwords longer than `MAX_ANALYZED_CHARS` are scored chunk by chunk and
+/// the guesses multiplied; a repetition reaching the end of a chunk is
+/// extended over the copies of its base that follow.
+pub fn estimate_guesses(password: &str) -> GuessEstimate {
+    let chars: Vec<char> = password.chars().collect();
+    let cardinality = cardinality(&chars);
+    let mut estimate = GuessEstimate {
+        guesses_log2: 0.0,
+        sequence: Vec::new(),
+    };
+    let mut offset = 0;
+    while offset < chars.len() {
+        let end = chars.len().min(offset + MAX_ANALYZED_CHARS);
+        let part = most_guessable(&chars[offset..end], cardinality);
+        estimate.guesses_log2 += part.guesses_log2;
+        estimate
+            .sequence
+            .extend(part.sequence.into_iter().map(|mut m| {
+                m.start += offset;
+                m.end += offset;
+                m
+            }));
+        offset = end;
+        if let Some(last) = estimate.sequence.last_mut()
+            && let MatchKind::Repeat { base, count } = &mut last.kind
+        {
+            let base_chars: Vec<char> = base.chars().collect();
+            let extra = chars[offset..]
+                .chunks_exact(base_chars.len())
+                .take_while(|copy| *copy == base_chars.as_slice())
+                .count();
+            if extra > 0 {
+                let grown = ((*count + extra) as f64 / *count as f64).log2();
+                *count += extra;
+                offset += extra * base_chars.len();
+                last.end = offset - 1;
+                last.token = chars[last.start..offset].iter().collect();
+                last.guesses_log2 += grown;
+                estimate.guesses_log2 += grown;
+            }
+        }
+    }
+    estimate
+}
+
+/// Charset size of the classes present in `chars` (as in the naive estimator).
+fn cardinality(chars: &[char]) -> usize {
+    class_charset_size(&chars.iter().collect::<String>()).max(1)
+}
+
+fn bruteforce_log2(length: usize, cardinality: usize) -> f64 {
+    length as f64 * (cardinality as f64).log2()
+}
+
+/// One step of the dynamic program: the cheapest way found so far to cover
+/// the password up to some position with a given number of matches.
+#[derive(Debug, Clone, Copy)]
+struct Cell {
+    /// `log2` of the product of the match guesses.
+    product: f64,
+    /// `log2` of the sequence guesses (with the length penalties).
+    total: f64,
+    /// Start of the last match.
+    start: usize,
+    /// Index into the pattern matches, or `None` for brute force.
+    pattern: Option<usize>,
+}
+
+/// The minimum-guesses decomposition of `chars`.
+fn most_guessable(chars: &[char], cardinality: usize) -> GuessEstimate {
+    let n = chars.len();
+    if n == 0 {
+        return GuessEstimate {
+            guesses_log2: 0.0,
+            sequence: Vec::new(),
+        };
+    }
+    let mut matches = omnimatch(chars);
+    for m in &mut matches {
+        // zxcvbn's floor for partial matches keeps tiny tokens from being free
+        if m.end - m.start + 1 < n {
+            let floor = if m.start == m.end {
+                MIN_SINGLE_CHAR_GUESSES
+            } else {
+                MIN_MULTI_CHAR_GUESSES
+            };
+            m.guesses_log2 = m.guesses_log2.max(floor.log2());
+        }
+    }
+    let mut by_end: Vec<Vec<usize>> = vec![Vec::new(); n];
+    for (index, m) in matches.iter().enumerate() {
+        by_end[m.end].push(index);
+    }
+
+    // optimal[k][l - 1]: best cover of chars[..=k] with l matches
+    let mut optimal: Vec<Vec<Option<Cell>>> = vec![vec![None; n]; n];
+    for k in 0..n {
+        for &index in &by_end[k] {
+            let m = &matches[index];
+            if m.start == 0 {
+                update(&mut optimal[k], 1, m.guesses_log2, 0, Some(index));
+            } else {
+                let previous: Vec<(usize, Cell)> = cells(&optimal[m.start - 1]);
+                for (l, cell) in previous {
+                    let product = cell.product + m.guesses_log2;
+                    update(&mut optimal[k], l + 1, product, m.start, Some(index));
+                }
+            }
+        }
+        update(
+            &mut optimal[k],
+            1,
+            bruteforce_log2(k + 1, cardinality),
+            0,
+            None,
+        );
+        for start in 1..=k {
+            let guesses = bruteforce_log2(k - start + 1, cardinality);
+            let previous: Vec<(usize, Cell)> = cells(&optimal[start - 1]);
+            for (l, cell) in previous {
+                // two adjacent brute-force runs are one run
+                if cell.pattern.is_some() {
+                    update(&mut optimal[k], l + 1, cell.product + guesses, start, None);
+                }
+            }
+        }
+    }
+
+    let (mut l, best) = cells(&optimal[n - 1])
+        .into_iter()
+        .min_by(|a, b| a.1.total.total_cmp(&b.1.total))
+        .expect("a single brute-force match always covers the password");
+    let mut sequence = Vec::with_capacity(l);
+    let mut k = n - 1;
+    loop {
+        let cell = optimal[k][l - 1].expect("backtracking follows stored cells");
+        sequence.push(match cell.pattern {
+            Some(index) => matches[index].clone(),
+            None => Match {
+                kind: MatchKind::Bruteforce { cardinality },
+                start: cell.start,
+                end: k,
+                token: chars[cell.start..=k].iter().collect(),
+                guesses_log2: bruteforce_log2(k - cell.start + 1, cardinality),
+            },
+        });
+        if cell.start == 0 {
+            break;
+        }
+        k = cell.start - 1;
+        l -= 1;
+    }
+    sequence.reverse();
+    GuessEstimate {
+        guesses_log2: best.total,
+        sequence,
+    }
+}
+
+/// The stored cells of one position, with their match counts.
+fn cells(row: &[Option<Cell>]) -> Vec<(usize, Cell)> {
+    row.iter()
+        .enumerate()
+        .filter_map(|(i, cell)| cell.map(|cell| (i + 1, cell)))
+        .collect()
+}
+
+/// Store a cover with `l` matches unless an existing cover with at most `l`
+/// matches is at least as cheap.
+fn update(row: &mut [Option<Cell>], l: usize, product: f64, start: usize, pattern: Option<usize>) {
+    // total = l! * product + D^(l - 1)
+    let penalty = (l - 1) as f64 * MIN_GUESSES_BEFORE_GROWING_SEQUENCE.log2();
+    let total = log2_add(log2_factorial(l) + product, penalty);
+    if row[..l]
+        .iter()
+        .flatten()
+        .any(|existing| existing.total <= total)
+    {
+        return;
+    }
+    row[l - 1] = Some(Cell {
+        product,
+        total,
+        start,
+        pattern,
+    });
+}
+
+/// `log2(2^a + 2^b)` without overflow.
+fn log2_add(a: f64, b: f64) -> f64 {
+    let (high, low) = if a > b { (a, b) } else { (b, a) };
+    high + (low - high).exp2().ln_1p() / std::f64::consts::LN_2
+}
+
+fn log2_factorial(n: usize) -> f64 {
+    (2..=n).map(|i| (i as f64).log2()).sum()
+}
+
+/// Every pattern match in `chars`.
+fn omnimatch(chars: &[char]) -> Vec<Match> {
+    let mut matches = Vec::new();
+    dictionary_matches(chars, &mut matches);
+    spatial_matches(chars, &mut matches);
+    repeat_matches(chars, &mut matches);
+    sequence_matches(chars, &mut matches);
+    date_matches(chars, &mut matches);
+    matches
+}
+
+/// Ranked dictionary: word to rank (1 is the most common).
+fn dictionary() -> &'static HashMap<String, usize> {
+    static DICTIONARY: OnceLock<HashMap<String, usize>> = OnceLock::new();
+    DICTIONARY.get_or_init(|| {
+        let mut ranks: HashMap<String, usize> = HashMap::new();
+        let mut insert = |word: &str, rank: usize| {
+            let entry = ranks.entry(word.to_lowercase()).or_insert(rank);
+            *entry = (*entry).min(rank);
+        };
+        for (i, word) in common_passwords().enumerate() {
+            insert(word, i + 1);
+        }
+        let english = bip39::english_words();
+        for word in &english {
+            insert(word, english.len());
+        }
+        if let Ok(eff) = BuiltinWordlist::EffLarge.words() {
+            for word in &eff {
+                insert(word, eff.len());
+            }
+        }
+        ranks
+    })
+}
+
+fn dictionary_matches(chars: &[char], out: &mut Vec<Match>) {
+    let dictionary = dictionary();
+    let lower: Vec<char> = chars.iter().map(|c| c.to_ascii_lowercase()).collect();
+    let n = chars.len();
+    let mut lookup = |candidate: &[char], subs: &[Option<char>], reversed: bool| {
+        for i in 0..n {
+            for j in i + 2..n {
+                let word: String = candidate[i..=j].iter().collect();
+                let Some(&rank) = dictionary.get(&word) else {
+                    continue;
+                };
+                let used: Vec<(char, char)> = if subs.is_empty() {
+                    Vec::new()
+                } else {
+                    (i..=j)
+                        .filter_map(|p| subs[p].map(|letter| (lower[p], letter)))
+                        .collect()
+                };
+                if !subs.is_empty() && used.is_empty() {
+                    // found without substitutions by the plain pass
+                    continue;
+                }
+                let (start, end) = if reversed {
+                    (n - 1 - j, n - 1 - i)
+                } else {
+                    (i, j)
+                };
+                let token: String = chars[start..=end].iter().collect();
+                let mut guesses = (rank as f64).log2() + uppercase_variations(&token);
+                if !used.is_empty() {
+                    guesses += l33t_variations(&token, &used);
+                }
+                if reversed {
+                    guesses += 1.0;
+                }
+                out.push(Match {
+                    kind: MatchKind::Dictionary {
+                        word,
+                        rank,
+                        l33t: !used.is_empty(),
+                        reversed,
+                    },
+                    start,
+                    end,
+                    token,
+                    guesses_log2: guesses,
+                });
+            }
+        }
+    };
+
+    lookup(&lower, &[], false);
+    let reversed: Vec<char> = lower.iter().rev().copied().collect();
+    if reversed != lower {
+        lookup(&reversed, &[], true);
+    }
+    for (subbed, subs) in l33t_candidates(&lower) {
+        lookup(&subbed, &subs, false);
+    }
+}
+
+/// Un-l33ted versions of `lower`, with the letter substituted at each position.
+fn l33t_candidates(lower: &[char]) -> Vec<(Vec<char>, Vec<Option<char>>)> {
+    // every l33t character present, with the letters it may stand for
+    let mut options: Vec<(char, Vec<char>)> = Vec::new();
+    for &c in lower {
+        if options.iter().any(|(seen, _)| *seen == c) {
+            continue;
+        }
+        let letters: Vec<char> = L33T_TABLE
+            .iter()
+            .filter(|(_, subs)| subs.contains(c))
+            .map(|(letter, _)| *letter)
+            .collect();
+        if !letters.is_empty() {
+            options.push((c, letters));
+        }
+    }
+    if options.is_empty() {
+        return Vec::new();
+    }
+    let mut combinations: Vec<Vec<(char, char)>> = vec![Vec::new()];
+    for (c, letters) in &options {
+        let mut next = Vec::new();
+        for combination in &combinations {
+            for &letter in letters {
+                let mut extended = combination.clone();
+                extended.push((*c, letter));
+                next.push(extended);
+            }
+        }
+        next.truncate(MAX_L33T_COMBINATIONS);
+        combinations = next;
+    }
+    combinations
+        .into_iter()
+        .map(|combination| {
+            let subs: Vec<Option<char>> = lower
+                .iter()
+                .map(|c| {
+                    combination
+                        .iter()
+                        .find(|(l33t, _)| l33t == c)
+                        .map(|(_, letter)| *letter)
+                })
+                .collect();
+            let subbed = lower
+                .iter()
+                .zip(&subs)
+                .map(|(&c, sub)| sub.unwrap_or(c))
+                .collect();
+            (subbed, subs)
+        })
+        .collect()
+}
+
+/// `log2` of the ways to capitalize `token` that an attacker would try first.
+fn uppercase_variations(token: &str) -> f64 {
+    let upper = token.chars().filter(|c| c.is_ascii_uppercase()).count();
+    let lower = token.chars().filter(|c| c.is_ascii_lowercase()).count();
+    if upper == 0 {
+        return 0.0;
+    }
+    let first = token.chars().next().is_some_and(|c| c.is_ascii_uppercase());
+    let last = token.chars().last().is_some_and(|c| c.is_ascii_uppercase());
+    if lower == 0 || (upper == 1 && (first || last)) {
+        return 1.0;
+    }
+    partial_variations(upper, lower)
+}
+
+/// `log2` of the l33t variations of `token` given the `(l33t, letter)` pairs used.
+fn l33t_variations(token: &str, used: &[(char, char)]) -> f64 {
+    let lower: Vec<char> = token.chars().map(|c| c.to_ascii_lowercase()).collect();
+    let mut total = 0.0;
+    let mut seen: Vec<(char, char)> = Vec::new();
+    for &(l33t, letter) in used {
+        if seen.contains(&(l33t, letter)) {
+            continue;
+        }
+        seen.push((l33t, letter));
+        let subbed = lower.iter().filter(|&&c| c == l33t).count();
+        let unsubbed = lower.iter().filter(|&&c| c == letter).count();
+        total += if unsubbed == 0 {
+            1.0
+        } else {
+            partial_variations(subbed, unsubbed)
+        };
+    }
+    total
+}
+
+/// `log2(sum_{i=1}^{min(a,b)} C(a + b, i))`.
+fn partial_variations(a: usize, b: usize) -> f64 {
+    (1..=a.min(b))
+        .map(|i| binomial(a + b, i))
+        .sum::<f64>()
+        .log2()
+}
+
+fn binomial(n: usize, k: usize) -> f64 {
+    (1..=k).fold(1.0, |acc, i| acc * (n + 1 - i) as f64 / i as f64)
+}
+
+/// A QWERTY key: its row and column in `QWERTY`.
+type Key = (usize, usize);
+
+/// Key of each character, and whether it needs shift.
+fn keyboard() -> &'static HashMap<char, (Key, bool)> {
+    static KEYBOARD: OnceLock<HashMap<char, (Key, bool)>> = OnceLock::new();
+    KEYBOARD.get_or_init(|| {
+        let mut keys = HashMap::new();
+        for (row, (plain, shifted)) in QWERTY.iter().enumerate() {
+            for (col, (p, s)) in plain.chars().zip(shifted.chars()).enumerate() {
+                if p != ' ' {
+                    keys.insert(p, ((row, col), false));
+                    keys.insert(s, ((row, col), true));
+                }
+            }
+        }
+        keys
+    })
+}
+
+/// Direction (0-5) from key `a` to the adjacent key `b`, if they are adjacent.
+fn direction(a: Key, b: Key) -> Option<usize> {
+    let (ar, ac) = (a.0 as isize, a.1 as isize);
+    let (br, bc) = (b.0 as isize, b.1 as isize);
+    const STEPS: [(isize, isize); 6] = [(0, -1), (-1, 0), (-1, 1), (0, 1), (1, 0), (1, -1)];
+    STEPS
+        .iter()
+        .position(|&(dr, dc)| ar + dr == br && ac + dc == bc)
+}
+
+/// Number of keys and their average number of neighbours.
+fn keyboard_stats() -> (f64, f64) {
+    let keys: Vec<Key> = QWERTY
+        .iter()
+        .enumerate()
+        .flat_map(|(row, (plain, _))| {
+            plain
+                .chars()
+                .enumerate()
+                .filter(|(_, c)| *c != ' ')
+                .map(move |(col, _)| (row, col))
+        })
+        .collect();
+    let degrees: usize = keys
+        .iter()
+        .map(|&a| keys.iter().filter(|&&b| direction(a, b).is_some()).count())
+        .sum();
+    (keys.len() as f64, degrees as f64 / keys.len() as f64)
+}
+
+fn spatial_matches(chars: &[char], out: &mut Vec<Match>) {
+    let keyboard = keyboard();
+    let (starts, degree) = keyboard_stats();
+    let mut i = 0;
+    while i + 2 < chars.len() {
+        let Some(&(mut key, first_shifted)) = keyboard.get(&chars[i]) else {
+            i += 1;
+            continue;
+        };
+        let mut shifted = usize::from(first_shifted);
+        let mut turns = 0;
+        let mut last_direction = None;
+        let mut j = i + 1;
+        while j < chars.len() {
+            let Some(&(next, is_shifted)) = keyboard.get(&chars[j]) else {
+                break;
+            };
+            let Some(dir) = direction(key, next) else {
+                break;
+            };
+            if last_direction != Some(dir) {
+                turns += 1;
+                last_direction = Some(dir);
+            }
+            shifted += usize::from(is_shifted);
+            key = next;
+            j += 1;
+        }
+        let length = j - i;
+        if length >= 3 {
+            let mut guesses = 0.0;
+            for len in 2..=length {
+                for t in 1..=turns.min(len - 1) {
+                    guesses += binomial(len - 1, t - 1) * starts * degree.powi(t as i32);
+                }
+            }
+            let mut guesses_log2 = guesses.log2();
+            let unshifted = length - shifted;
+            if shifted > 0 {
+                guesses_log2 += if unshifted == 0 {
+                    1.0
+                } else {
+                    partial_variations(shifted, unshifted)
+                };
+            }
+            out.push(Match {
+                kind: MatchKind::Spatial { turns, shifted },
+                start: i,
+                end: j - 1,
+                token: chars[i..j].iter().collect(),
+                guesses_log2,
+            });
+            i = j - 1;
+        } else {
+            i += 1;
+        }
+    }
+}
+
+fn repeat_matches(chars: &[char], out: &mut Vec<Match>) {
+    let n = chars.len();
+    for i in 0..n {
+        // longest repetition starting here, preferring the shortest base
+        let mut best: Option<(usize, usize)> = None;
+        for base in 1..=(n - i) / 2 {
+            let mut count = 1;
+            while i + (count + 1) * base <= n
+                && chars[i + count * base..i + (count + 1) * base] == chars[i..i + base]
+            {
+                count += 1;
+            }
+            if count >= 2 && best.is_none_or(|(b, c)| base * count > b * c) {
+                best = Some((base, count));
+            }
+        }
+        let Some((base, count)) = best else {
+            continue;
+        };
+        if i > 0 && chars[i - 1..i - 1 + base] == chars[i..i + base] {
+            // already covered by the repetition starting one character earlier
+            continue;
+        }
+        let base_chars = &chars[i..i + base];
+        let base_guesses = most_guessable(base_chars, cardinality(base_chars)).guesses_log2;
+        out.push(Match {
+            kind: MatchKind::Repeat {
+                base: base_chars.iter().collect(),
+                count,
+            },
+            start: i,
+            end: i + base * count - 1,
+            token: chars[i..i + base * count].iter().collect(),
+            guesses_log2: base_guesses + (count as f64).log2(),
+        });
+    }
+}
+
+fn sequence_matches(chars: &[char], out: &mut Vec<Match>) {
+    let n = chars.len();
+    let mut i = 0;
+    while i + 1 < n {
+        let delta = chars[i + 1] as i64 - chars[i] as i64;
+        let mut j = i + 1;
+        while j + 1 < n && chars[j + 1] as i64 - chars[j] as i64 == delta {
+            j += 1;
+        }
+        let run = &chars[i..=j];
+        let class = CharClass::of(run[0]);
+        if run.len() >= 3
+            && (1..=5).contains(&delta.abs())
+            && class != CharClass::Symbol
+            && run.iter().all(|&c| CharClass::of(c) == class)
+        {
+            let base: f64 = if "aAzZ019".contains(run[0]) {
+                4.0
+            } else if class == CharClass::Digit {
+                10.0
+            } else {
+                26.0
+            };
+            let direction = if delta < 0 { 2.0 } else { 1.0 };
+            out.push(Match {
+                kind: MatchKind::Sequence {
+                    ascending: delta > 0,
+                },
+                start: i,
+                end: j,
+                token: run.iter().collect(),
+                guesses_log2: (base * direction * run.len() as f64).log2(),
+            });
+        }
+        i = j;
+    }
+}
+
+/// The current year, used as the reference point for dates.
+fn reference_year() -> i32 {
+    (1970.0 + unix_millis() as f64 / 1000.0 / 31_556_952.0) as i32
+}
+
+fn year_space(year: i32) -> f64 {
+    ((year - reference_year()).abs() as f64).max(MIN_YEAR_SPACE)
+}
+
+fn date_matches(chars: &[char], out: &mut Vec<Match>) {
+    let n = chars.len();
+    for i in 0..n {
+        for j in i + 3..n.min(i + 10) {
+            let token: String = chars[i..=j].iter().collect();
+            if j - i == 3
+                && let Ok(year) = token.parse::<i32>()
+                && (1900..=2099).contains(&year)
+            {
+                out.push(Match {
+                    kind: MatchKind::Year { year },
+                    start: i,
+                    end: j,
+                    token: token.clone(),
+                    guesses_log2: year_space(year).log2(),
+                });
+            }
+            if let Some((year, separator)) = parse_date(&token) {
+                let separators = if separator { 4.0 } else { 1.0 };
+                out.push(Match {
+                    kind: MatchKind::Date { year, separator },
+                    start: i,
+                    end: j,
+                    token,
+                    guesses_log2: (year_space(year) * 365.0 * separators).log2(),
+                });
+            }
+        }
+    }
+}
+
+/// Interpret `token` as a day, month and year, returning the year closest to
+/// now and whether separators were used.
+fn parse_date(token: &str) -> Option<(i32, bool)> {
+    let separator = token.chars().find(|c| DATE_SEPARATORS.contains(*c));
+    let parts: Vec<&str> = match separator {
+        Some(sep) => {
+            let parts: Vec<&str> = token.split(sep).collect();
+            if parts.len() != 3 {
+                return None;
+            }
+            parts
+        }
+        None => {
+            if !(4..=8).contains(&token.len()) {
+                return None;
+            }
+            return digit_splits(token)
+                .filter_map(|parts| date_year(&parts))
+                .min_by_key(|year| (year - reference_year()).abs())
+                .map(|year| (year, false));
+        }
+    };
+    if parts
+        .iter()
+        .any(|p| p.is_empty() || p.len() > 4 || !p.bytes().all(|b| b.is_ascii_digit()))
+    {
+        return None;
+    }
+    date_year(&[parts[0], parts[1], parts[2]]).map(|year| (year, true))
+}
+
+/// Every split of an all-digit `token` into three parts of 1 to 4 digits.
+fn digit_splits(token: &str) -> impl Iterator<Item = [&str; 3]> {
+    let digits = token.bytes().all(|b| b.is_ascii_digit());
+    let n = token.len();
+    (1..=4usize)
+        .flat_map(move |a| (1..=4usize).map(move |b| (a, b)))
+        .filter(move |&(a, b)| digits && a + b < n && n - a - b <= 4)
+        .map(move |(a, b)| [&token[..a], &token[a..a + b], &token[a + b..]])
+}
+
+/// The year of `parts` read as year-month-day, day-month-year or
+/// month-day-year, if any reading is a valid date.
+fn date_year(parts: &[&str; 3]) -> Option<i32> {
+    let day_month = |a: &str, b: &str| {
+        let valid = |d: &str, m: &str| {
+            d.len() <= 2
+                && m.len() <= 2
+                && d.parse::<u32>().is_ok_and(|d| (1..=31).contains(&d))
+                && m.parse::<u32>().is_ok_and(|m| (1..=12).contains(&m))
+        };
+        valid(a, b) || valid(b, a)
+    };
+    let year = |y: &str| -> Option<i32> {
+        let value: i32 = y.parse().ok()?;
+        match y.len() {
+            2 if value > 50 => Some(1900 + value),
+            2 => Some(2000 + value),
+            4 if (1000..=2050).contains(&value) => Some(value),
+            _ => None,
+        }
+    };
+    let mut years = Vec::new();
+    if day_month(parts[1], parts[2])
+        && let Some(y) = year(parts[0])
+    {
+        years.push(y);
+    }
+    if day_month(parts[0], parts[1])
+        && let Some(y) = year(parts[2])
+    {
+        years.push(y);
+    }
+    years
+        .into_iter()
+        .min_by_key(|y| (y - reference_year()).abs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(password: &str) -> Vec<&'static str> {
+        estimate_guesses(password)
+            .sequence
+            .iter()
+            .map(|m| m.kind.name())
+            .collect()
+    }
+
+    #[test]
+    fn test_weak_passwords_are_cheap() {
+        for password in [
+            "Password1!",
+            "Aaaaaaaaa1!",
+            "p@ssw0rd",
+            "qwertyuiop",
+            "drowssap",
+        ] {
+            let estimate = estimate_guesses(password);
+            // "very weak" in the check verdicts
+            assert!(estimate.guesses_log2 < 40.0, "{} {:?}", password, estimate);
+        }
+        let random = estimate_guesses("hV9$qL2#xZ7!mK4@pW");
+        assert!(random.guesses_log2 > 100.0, "{:?}", random);
+    }
+
+    #[test]
+    fn test_patterns_are_recognized() {
+        assert_eq!(kinds("password"), ["dictionary"]);
+        assert_eq!(kinds("abcdefgh"), ["sequence"]);
+        assert_eq!(kinds("zxcvbnm"), ["dictionary"]);
+        assert_eq!(kinds("qazxswedc"), ["spatial"]);
+        assert_eq!(kinds("xyzxyzxyz"), ["repeat"]);
+        assert_eq!(kinds("1987-06-15"), ["date"]);
+        let leet = estimate_guesses("P4ssw0rd");
+        assert!(matches!(
+            &leet.sequence[0].kind,
+            MatchKind::Dictionary { l33t: true, .. }
+        ));
+        let reversed = estimate_guesses("drowssap");
+        assert!(matches!(
+            &reversed.sequence[0].kind,
+            MatchKind::Dictionary { reversed: true, .. }
+        ));
+    }
+
+    #[test]
+    fn test_long_inputs_are_matched_past_the_first_chunk() {
+        let a300 = estimate_guesses(&"a".repeat(300));
+        assert_eq!(kinds(&"a".repeat(300)), ["repeat"]);
+        assert_eq!(a300.sequence[0].end, 299);
+        assert!(a300.guesses_log2 < 20.0, "{:?}", a300);
+        let ab80 = estimate_guesses(&"ab".repeat(80));
+        assert!(
+            matches!(&ab80.sequence[0].kind, MatchKind::Repeat { count: 80, .. }),
+            "{:?}",
+            ab80
+        );
+        assert!(ab80.guesses_log2 < 30.0, "{:?}", ab80);
+
+        // later chunks still find patterns, and the cover stays contiguous
+        let mixed = format!("{}password", "hV9$qL2#xZ7!mK4@pW".repeat(6));
+        let estimate = estimate_guesses(&mixed);
+        assert_eq!(estimate.sequence.last().unwrap().kind.name(), "dictionary");
+        for pair in estimate.sequence.windows(2) {
+            assert_eq!(pair[0].end + 1, pair[1].start);
+        }
+        assert_eq!(estimate.sequence.last().unwrap().end, mixed.len() - 1);
+    }
+
+    #[test]
+    fn test_dates_and_variations() {
+        assert!(parse_date("15061987").is_some());
+        assert_eq!(parse_date("1987/06/15"), Some((1987, true)));
+        assert_eq!(parse_date("99/99/99"), None);
+        assert_eq!(uppercase_variations("password"), 0.0);
+        assert_eq!(uppercase_variations("Password"), 1.0);
+        assert_eq!(uppercase_variations("PASSWORD"), 1.0);
+        assert!(uppercase_variations("PaSsWoRd") > 1.0);
+        assert!((log2_add(3.0, 3.0) - 4.0).abs() < 1e-12);
+    }
+}
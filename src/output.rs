@@ -0,0 +1,256 @@
+//! Machine-readable records for `--format json|csv`.
+//!
+//! `generate` emits one `GeneratedRecord` per item and `check`/`profile` one
+//! `CheckRecord` per candidate. JSON is a single document (an array for
+//! `generate` and batch mode, an object for a single candidate); CSV is a
+//! header row followed by one RFC 4180 row per record. Candidates appear in
+//! check records only when asked for, so reports can be shared safely.
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::Serialize;
+
+use crate::entropy::EntropyProfile;
+use crate::error::GenixError;
+use crate::policy::ComplianceReport;
+
+/// Verdicts in increasing strength, then the breach override.
+pub const VERDICTS: [&str; 6] = [
+    "very weak",
+    "weak",
+    "fair",
+    "strong",
+    "very strong",
+    "compromised",
+];
+
+/// Verdict for an estimate of `bits`.
+pub fn verdict(bits: f64) -> &'static str {
+    match bits {
+        b if b < 40.0 => "very weak",
+        b if b < 64.0 => "weak",
+        b if b < 80.0 => "fair",
+        b if b < 128.0 => "strong",
+        _ => "very strong",
+    }
+}
+
+/// How the CLI prints results.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum OutputFormat {
+    /// Human-readable lines.
+    #[default]
+    Text,
+    /// One JSON document.
+    Json,
+    /// A header row and one row per record.
+    Csv,
+}
+
+impl OutputFormat {
+    /// Every format, in the order shown in help text.
+    pub const ALL: [OutputFormat; 3] = [OutputFormat::Text, OutputFormat::Json, OutputFormat::Csv];
+
+    /// The command-line name of the format.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            OutputFormat::Text => "text",
+            OutputFormat::Json => "json",
+            OutputFormat::Csv => "csv",
+        }
+    }
+}
+
+impl fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for OutputFormat {
+    type Err = GenixError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        OutputFormat::ALL
+            .into_iter()
+            .find(|format| format.as_str() == s)
+            .ok_or_else(|| GenixError::UnknownFormat(s.to_string()))
+    }
+}
+
+/// One generated item.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GeneratedRecord {
+    pub value: String,
+    pub style: String,
+    /// Effective length (characters, bytes or words, as for `--length`)
+    pub length: usize,
+    /// Exact entropy of the generator, not an estimate of the value
+    pub entropy_bits: f64,
+    pub verdict: &'static str,
+}
+
+impl GeneratedRecord {
+    /// CSV header matching `csv_row`.
+    pub const CSV_HEADER: &'static str = "value,style,length,entropy_bits,verdict";
+
+    /// This record as one CSV row.
+    pub fn csv_row(&self) -> String {
+        csv_row(&[
+            self.value.clone(),
+            self.style.clone(),
+            self.length.to_string(),
+            format!("{:.2}", self.entropy_bits),
+            self.verdict.to_string(),
+        ])
+    }
+}
+
+/// One checked or profiled candidate.
+#[derive(Debug, Serialize)]
+pub struct CheckRecord<'a> {
+    /// Line number in batch mode
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<usize>,
+    /// The candidate itself, only when requested (`--show`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<&'a str>,
+    pub style: String,
+    /// Characters, or words for passphrases
+    pub length: usize,
+    pub entropy_bits: f64,
+    pub verdict: &'static str,
+    /// Times seen in the breach database, when one was given
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub breach_count: Option<u64>,
+    /// The full profile (`profile` only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub profile: Option<&'a EntropyProfile>,
+    /// Per-rule results, when checked against a policy
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub policy: Option<&'a ComplianceReport>,
+}
+
+impl CheckRecord<'_> {
+    /// CSV header matching `csv_row`; `profile` adds guesses and crack times,
+    /// `policy` the compliance result and the failed rules.
+    pub fn csv_header(profile: bool, policy: bool) -> String {
+        let mut header = "line,value,style,length,entropy_bits,verdict,breach_count".to_string();
+        if profile {
+            header.push_str(
+                ",guesses_log2,online_throttled_secs,online_unthrottled_secs,offline_slow_secs,offline_fast_secs",
+            );
+        }
+        if policy {
+            header.push_str(",policy,failed_rules");
+        }
+        header
+    }
+
+    /// This record as one CSV row; absent values are empty fields.
+    pub fn csv_row(&self) -> String {
+        let optional = |value: Option<String>| value.unwrap_or_default();
+        let mut fields = vec![
+            optional(self.line.map(|line| line.to_string())),
+            optional(self.value.map(str::to_string)),
+            self.style.clone(),
+            self.length.to_string(),
+            format!("{:.2}", self.entropy_bits),
+            self.verdict.to_string(),
+            optional(self.breach_count.map(|count| count.to_string())),
+        ];
+        if let Some(profile) = self.profile {
+            fields.push(optional(
+                profile
+                    .guesses
+                    .as_ref()
+                    .map(|guesses| format!("{:.2}", guesses.guesses_log2)),
+            ));
+            let times = profile.crack_times;
+            for seconds in [
+                times.online_throttled,
+                times.online_unthrottled,
+                times.offline_slow,
+                times.offline_fast,
+            ] {
+                fields.push(format!("{:.3e}", seconds));
+            }
+        }
+        if let Some(report) = self.policy {
+            let result = if report.passed() { "pass" } else { "fail" };
+            fields.push(result.to_string());
+            fields.push(report.failed_rules().join(";"));
+        }
+        csv_row(&fields)
+    }
+}
+
+/// Join `fields` with commas, quoting those that contain `,`, `"` or a line
+/// break (RFC 4180).
+pub fn csv_row(fields: &[String]) -> String {
+    fields
+        .iter()
+        .map(|field| {
+            if field.contains([',', '"', '\n', '\r']) {
+                format!("\"{}\"", field.replace('"', "\"\""))
+            } else {
+                field.clone()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entropy::{EstimateOptions, Estimator, estimate_entropy_detailed_with};
+    use crate::generate::Style;
+
+    #[test]
+    fn test_csv_quoting() {
+        let row = csv_row(&["a,b".into(), "say \"hi\"".into(), "plain".into()]);
+        assert_eq!(row, "\"a,b\",\"say \"\"hi\"\"\",plain");
+        assert_eq!("csv".parse::<OutputFormat>().unwrap(), OutputFormat::Csv);
+        assert!(matches!(
+            "yaml".parse::<OutputFormat>(),
+            Err(GenixError::UnknownFormat(_))
+        ));
+    }
+
+    #[test]
+    fn test_check_record_json_and_csv() {
+        let options = EstimateOptions {
+            estimator: Estimator::Guesses,
+            ..EstimateOptions::default()
+        };
+        let profile =
+            estimate_entropy_detailed_with("Password1!", Style::Random, &options).unwrap();
+        let record = CheckRecord {
+            line: None,
+            value: None,
+            style: Style::Random.to_string(),
+            length: profile.length,
+            entropy_bits: profile.bits,
+            verdict: verdict(profile.bits),
+            breach_count: Some(3),
+            profile: Some(&profile),
+            policy: None,
+        };
+        let json: serde_json::Value = serde_json::to_value(&record).unwrap();
+        assert!(json.get("value").is_none());
+        assert_eq!(json["verdict"], "very weak");
+        assert_eq!(json["breach_count"], 3);
+        assert_eq!(
+            json["profile"]["guesses"]["sequence"][0]["kind"]["pattern"],
+            "dictionary"
+        );
+        let row = record.csv_row();
+        assert_eq!(
+            row.split(',').count(),
+            CheckRecord::csv_header(true, false).split(',').count()
+        );
+        assert!(row.starts_with(",,random,10,"));
+    }
+}
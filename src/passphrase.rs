@@ -0,0 +1,320 @@
+//! Passphrase formatting and parsing.
+//!
+//! `PassphraseFormat` controls how the words chosen by the `passphrase` style
+//! are joined: the separator (fixed or random), capitalization, and an optional
+//! digit and symbol appended to randomly chosen words. Every random choice adds
+//! entropy on top of the words themselves; `PassphraseFormat::extra_entropy`
+//! reports exactly how much.
+//!
+//! `ParsedPassphrase` goes the other way for `check`/`profile`: it splits an
+//! existing passphrase back into words, separators and extras so the estimator
+//! can credit the same formatting choices.
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::charset::SYMBOLS;
+use crate::error::GenixError;
+
+/// Separators drawn from when `random_separator` is enabled.
+pub const RANDOM_SEPARATORS: &str = "-_.:;,!#$%&*+=@~/";
+
+/// How the words of a passphrase are capitalized.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Capitalize {
+    /// Leave words as they appear in the wordlist.
+    #[default]
+    None,
+    /// Uppercase the first letter of every word.
+    First,
+    /// Uppercase every letter.
+    All,
+    /// Independently uppercase the first letter of each word (1 bit per word).
+    Random,
+}
+
+impl Capitalize {
+    /// Every mode, in the order shown in help text.
+    pub const ALL: [Capitalize; 4] = [
+        Capitalize::None,
+        Capitalize::First,
+        Capitalize::All,
+        Capitalize::Random,
+    ];
+
+    /// The command-line name of the mode.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Capitalize::None => "none",
+            Capitalize::First => "first",
+            Capitalize::All => "all",
+            Capitalize::Random => "random",
+        }
+    }
+}
+
+impl fmt::Display for Capitalize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Capitalize {
+    type Err = GenixError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Capitalize::ALL
+            .into_iter()
+            .find(|mode| mode.as_str() == s)
+            .ok_or_else(|| GenixError::UnknownCapitalize(s.to_string()))
+    }
+}
+
+/// Formatting options for the `passphrase` style.
+///
+/// The default (`-` separator, no capitalization, no extras) reproduces the
+/// historical `word-word-word` output.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PassphraseFormat {
+    /// Separator placed between words (ignored when `random_separator` is set).
+    pub separator: String,
+    /// Pick each separator uniformly from `RANDOM_SEPARATORS`.
+    pub random_separator: bool,
+    /// Capitalization applied to each word.
+    pub capitalize: Capitalize,
+    /// Append a random digit to a randomly chosen word.
+    pub add_digit: bool,
+    /// Append a random symbol (from `charset::SYMBOLS`) to a randomly chosen word.
+    pub add_symbol: bool,
+}
+
+impl Default for PassphraseFormat {
+    fn default() -> Self {
+        PassphraseFormat {
+            separator: "-".into(),
+            random_separator: false,
+            capitalize: Capitalize::None,
+            add_digit: false,
+            add_symbol: false,
+        }
+    }
+}
+
+impl PassphraseFormat {
+    /// Entropy (bits) added by the format's random choices for a passphrase of
+    /// `word_count` words, on top of the words themselves.
+    pub fn extra_entropy(&self, word_count: usize) -> f64 {
+        format_entropy(
+            word_count,
+            self.capitalize == Capitalize::Random,
+            self.random_separator,
+            usize::from(self.add_digit),
+            usize::from(self.add_symbol),
+        )
+    }
+}
+
+/// Shared entropy formula for generated and parsed passphrases.
+fn format_entropy(
+    word_count: usize,
+    random_case: bool,
+    random_separators: bool,
+    digits: usize,
+    symbols: usize,
+) -> f64 {
+    if word_count == 0 {
+        return 0.0;
+    }
+    let n = word_count as f64;
+    let mut bits = 0.0;
+    if random_case {
+        bits += n;
+    }
+    if random_separators {
+        bits += (n - 1.0) * (RANDOM_SEPARATORS.chars().count() as f64).log2();
+    }
+    // each extra is a value choice plus a choice of which word it follows
+    bits += digits as f64 * (10f64.log2() + n.log2());
+    bits += symbols as f64 * ((SYMBOLS.chars().count() as f64).log2() + n.log2());
+    bits
+}
+
+/// A passphrase split back into its parts.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ParsedPassphrase {
+    /// The words, as written (including capitalization).
+    pub words: Vec<String>,
+    /// Separator found between each pair of adjacent words.
+    pub separators: Vec<String>,
+    /// Number of digits appended to words.
+    pub digits: usize,
+    /// Number of symbols appended to words (beyond the separators).
+    pub symbols: usize,
+}
+
+impl ParsedPassphrase {
+    /// Split `s` into words, separators and appended digits/symbols.
+    ///
+    /// Words are runs of letters; a lowercase-to-uppercase step also starts a
+    /// new word so `CorrectHorse` splits without a separator. Between two
+    /// words, leading digits are appended digits. With `separator` given, the
+    /// run must end in it and the remaining characters are appended symbols;
+    /// otherwise the last character is taken as the separator. Anything after
+    /// the last word counts as appended digits/symbols.
+    pub fn parse(s: &str, separator: Option<&str>) -> ParsedPassphrase {
+        // Alternating runs of letters (words) and non-letters (gaps); a case
+        // step inside a word inserts an empty gap.
+        let mut segments: Vec<(bool, String)> = Vec::new();
+        for c in s.chars() {
+            let is_word = c.is_alphabetic();
+            match segments.last_mut() {
+                Some((last_is_word, text)) if *last_is_word == is_word => {
+                    let case_step = is_word
+                        && c.is_uppercase()
+                        && text.chars().last().is_some_and(char::is_lowercase);
+                    if case_step {
+                        segments.push((false, String::new()));
+                        segments.push((true, c.to_string()));
+                    } else {
+                        text.push(c);
+                    }
+                }
+                _ => segments.push((is_word, c.to_string())),
+            }
+        }
+
+        let mut parsed = ParsedPassphrase::default();
+        let mut pending_gap: Option<String> = None;
+        for (is_word, text) in segments {
+            if !is_word {
+                pending_gap = Some(text);
+                continue;
+            }
+            if let Some(gap) = pending_gap.take() {
+                if parsed.words.is_empty() {
+                    parsed.count_extras(&gap);
+                } else {
+                    let sep = parsed.split_gap(&gap, separator);
+                    parsed.separators.push(sep);
+                }
+            }
+            parsed.words.push(text);
+        }
+        if let Some(gap) = pending_gap {
+            parsed.count_extras(&gap);
+        }
+        parsed
+    }
+
+    /// Split an inter-word gap into appended extras and the separator.
+    fn split_gap(&mut self, gap: &str, separator: Option<&str>) -> String {
+        let rest = gap.trim_start_matches(|c: char| c.is_ascii_digit());
+        self.digits += gap.len() - rest.len();
+        let sep_start = match separator {
+            Some(sep) if rest.ends_with(sep) => rest.len() - sep.len(),
+            _ => rest.char_indices().last().map_or(0, |(i, _)| i),
+        };
+        self.count_extras(&rest[..sep_start]);
+        rest[sep_start..].to_string()
+    }
+
+    /// Count digits and symbols in `extras`.
+    fn count_extras(&mut self, extras: &str) {
+        for c in extras.chars() {
+            if c.is_ascii_digit() {
+                self.digits += 1;
+            } else {
+                self.symbols += 1;
+            }
+        }
+    }
+
+    /// The capitalization the words are consistent with. Mixed
+    /// capitalization is reported as `Capitalize::Random`.
+    pub fn capitalization(&self) -> Capitalize {
+        let is_first = |w: &String| {
+            let mut chars = w.chars();
+            chars.next().is_some_and(char::is_uppercase) && chars.all(|c| !c.is_uppercase())
+        };
+        let is_all = |w: &String| w.chars().all(|c| !c.is_lowercase());
+        let is_none = |w: &String| w.chars().all(|c| !c.is_uppercase());
+        if self.words.iter().all(is_none) {
+            Capitalize::None
+        } else if self.words.iter().all(is_all) {
+            Capitalize::All
+        } else if self.words.iter().all(is_first) {
+            Capitalize::First
+        } else {
+            Capitalize::Random
+        }
+    }
+
+    /// True if the separators are not all identical.
+    pub fn has_random_separators(&self) -> bool {
+        self.separators.windows(2).any(|pair| pair[0] != pair[1])
+    }
+
+    /// Entropy (bits) credited to the formatting choices found, using the same
+    /// formula as `PassphraseFormat::extra_entropy`.
+    pub fn extra_entropy(&self) -> f64 {
+        format_entropy(
+            self.words.len(),
+            self.capitalization() == Capitalize::Random,
+            self.has_random_separators(),
+            self.digits,
+            self.symbols,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_default_format() {
+        let p = ParsedPassphrase::parse("apple-banana-orange", None);
+        assert_eq!(p.words, vec!["apple", "banana", "orange"]);
+        assert_eq!(p.separators, vec!["-", "-"]);
+        assert_eq!((p.digits, p.symbols), (0, 0));
+        assert_eq!(p.extra_entropy(), 0.0);
+    }
+
+    #[test]
+    fn test_parse_extras_and_random_separators() {
+        let p = ParsedPassphrase::parse("Apple7!#banana%Orange", None);
+        assert_eq!(p.words, vec!["Apple", "banana", "Orange"]);
+        assert_eq!(p.separators, vec!["#", "%"]);
+        assert_eq!((p.digits, p.symbols), (1, 1));
+        assert_eq!(p.capitalization(), Capitalize::Random);
+        assert!(p.has_random_separators());
+        let format = PassphraseFormat {
+            random_separator: true,
+            capitalize: Capitalize::Random,
+            add_digit: true,
+            add_symbol: true,
+            ..PassphraseFormat::default()
+        };
+        assert!((p.extra_entropy() - format.extra_entropy(3)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_parse_explicit_and_empty_separator() {
+        let p = ParsedPassphrase::parse("one :: two :: three9", Some(" :: "));
+        assert_eq!(p.words, vec!["one", "two", "three"]);
+        assert_eq!(p.separators, vec![" :: ", " :: "]);
+        assert_eq!(p.digits, 1);
+
+        let camel = ParsedPassphrase::parse("CorrectHorseBattery", Some(""));
+        assert_eq!(camel.words, vec!["Correct", "Horse", "Battery"]);
+        assert_eq!(camel.capitalization(), Capitalize::First);
+    }
+
+    #[test]
+    fn test_capitalize_round_trip() {
+        for mode in Capitalize::ALL {
+            assert_eq!(mode.to_string().parse::<Capitalize>().unwrap(), mode);
+        }
+        assert!("title".parse::<Capitalize>().is_err());
+    }
+}
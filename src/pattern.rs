@@ -0,0 +1,176 @@
+//! Template mini-language for the `pattern` style.
+//!
+//! A pattern such as `Cvccvc-9999-ss` or `XXXX-XXXX-XXXX` describes the exact
+//! shape of the output. Each placeholder is replaced by a uniformly chosen
+//! member of its class; every other character is copied through unchanged.
+//!
+//! | Placeholder | Replaced by                         |
+//! |-------------|-------------------------------------|
+//! | `a` / `A`   | lowercase / uppercase letter        |
+//! | `c` / `C`   | lowercase / uppercase consonant     |
+//! | `v` / `V`   | lowercase / uppercase vowel         |
+//! | `9`         | digit                               |
+//! | `h` / `H`   | lowercase / uppercase hex digit     |
+//! | `x` / `X`   | lowercase / uppercase letter or digit |
+//! | `s`         | symbol (`charset::SYMBOLS`)         |
+//! | `*`         | any character of the default pool   |
+//! | `w` / `W`   | word / capitalized word from the wordlist |
+//! | `\`         | copy the next character literally   |
+//!
+//! Because every placeholder is an independent uniform choice, the entropy of
+//! a pattern is exact: see `entropy::pattern_entropy`.
+
+use crate::charset::{DIGITS, LOWER, SYMBOLS, UPPER};
+use crate::error::GenixError;
+
+const LOWER_CONSONANTS: &str = "bcdfghjklmnpqrstvwxyz";
+const UPPER_CONSONANTS: &str = "BCDFGHJKLMNPQRSTVWXYZ";
+const LOWER_VOWELS: &str = "aeiou";
+const UPPER_VOWELS: &str = "AEIOU";
+const LOWER_HEX: &str = "0123456789abcdef";
+const UPPER_HEX: &str = "0123456789ABCDEF";
+
+/// A single element of a parsed pattern.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Token {
+    /// A uniformly chosen character from the given set.
+    Class(Vec<char>),
+    /// A uniformly chosen wordlist entry, optionally capitalized.
+    Word { capitalize: bool },
+    /// A character copied verbatim.
+    Literal(char),
+}
+
+/// A parsed template.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pattern {
+    tokens: Vec<Token>,
+}
+
+impl Pattern {
+    /// Parse `template` into tokens.
+    ///
+    /// # Errors
+    /// Returns `GenixError::InvalidPattern` for an empty template or a
+    /// trailing `\` with nothing to escape.
+    pub fn parse(template: &str) -> Result<Pattern, GenixError> {
+        if template.is_empty() {
+            return Err(GenixError::InvalidPattern("pattern is empty".into()));
+        }
+        let mut tokens = Vec::new();
+        let mut chars = template.chars();
+        while let Some(c) = chars.next() {
+            let token = match c {
+                '\\' => match chars.next() {
+                    Some(escaped) => Token::Literal(escaped),
+                    None => {
+                        return Err(GenixError::InvalidPattern(
+                            "trailing '\\' has nothing to escape".into(),
+                        ));
+                    }
+                },
+                'w' => Token::Word { capitalize: false },
+                'W' => Token::Word { capitalize: true },
+                other => match class_chars(other) {
+                    Some(set) => Token::Class(set.chars().collect()),
+                    None => Token::Literal(other),
+                },
+            };
+            tokens.push(token);
+        }
+        Ok(Pattern { tokens })
+    }
+
+    /// Alternating consonant/vowel pattern of `length` characters, starting
+    /// with a consonant (`cvcv...`). Used by the `pronounceable` style.
+    pub fn pronounceable(length: usize) -> Pattern {
+        let tokens = (0..length)
+            .map(|i| {
+                let set = if i % 2 == 0 {
+                    LOWER_CONSONANTS
+                } else {
+                    LOWER_VOWELS
+                };
+                Token::Class(set.chars().collect())
+            })
+            .collect();
+        Pattern { tokens }
+    }
+
+    /// The parsed tokens, in output order.
+    pub fn tokens(&self) -> &[Token] {
+        &self.tokens
+    }
+
+    /// True if the pattern contains a word placeholder and so needs a wordlist.
+    pub fn uses_words(&self) -> bool {
+        self.tokens.iter().any(|t| matches!(t, Token::Word { .. }))
+    }
+}
+
+/// Character set for a single-character placeholder, if `c` is one.
+fn class_chars(c: char) -> Option<String> {
+    let set = match c {
+        'a' => LOWER.to_string(),
+        'A' => UPPER.to_string(),
+        'c' => LOWER_CONSONANTS.to_string(),
+        'C' => UPPER_CONSONANTS.to_string(),
+        'v' => LOWER_VOWELS.to_string(),
+        'V' => UPPER_VOWELS.to_string(),
+        '9' => DIGITS.to_string(),
+        'h' => LOWER_HEX.to_string(),
+        'H' => UPPER_HEX.to_string(),
+        'x' => format!("{}{}", LOWER, DIGITS),
+        'X' => format!("{}{}", UPPER, DIGITS),
+        's' => SYMBOLS.to_string(),
+        '*' => format!("{}{}{}{}", UPPER, LOWER, DIGITS, SYMBOLS),
+        _ => return None,
+    };
+    Some(set)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_classes_and_literals() {
+        let p = Pattern::parse("Cv-9\\9w").unwrap();
+        let tokens = p.tokens();
+        assert_eq!(tokens.len(), 6);
+        assert!(matches!(&tokens[0], Token::Class(set) if set.len() == 21));
+        assert!(matches!(&tokens[1], Token::Class(set) if set.len() == 5));
+        assert_eq!(tokens[2], Token::Literal('-'));
+        assert!(matches!(&tokens[3], Token::Class(set) if set.len() == 10));
+        assert_eq!(tokens[4], Token::Literal('9'));
+        assert_eq!(tokens[5], Token::Word { capitalize: false });
+        assert!(p.uses_words());
+    }
+
+    #[test]
+    fn test_pronounceable_alternates() {
+        let p = Pattern::pronounceable(5);
+        let sizes: Vec<usize> = p
+            .tokens()
+            .iter()
+            .map(|t| match t {
+                Token::Class(set) => set.len(),
+                _ => 0,
+            })
+            .collect();
+        assert_eq!(sizes, vec![21, 5, 21, 5, 21]);
+        assert!(Pattern::pronounceable(0).tokens().is_empty());
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(matches!(
+            Pattern::parse(""),
+            Err(GenixError::InvalidPattern(_))
+        ));
+        assert!(matches!(
+            Pattern::parse("XX\\"),
+            Err(GenixError::InvalidPattern(_))
+        ));
+    }
+}
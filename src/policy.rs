@@ -0,0 +1,665 @@
+//! Password policies: character-class minimums and compliance rules.
+//!
+//! A `Policy` such as "at least 2 digits, 1 symbol, 1 uppercase" constrains the
+//! `random` style. Compliant passwords are drawn *uniformly* from the set of
+//! all compliant strings over the pool, so the policy never biases which
+//! compliant password you get:
+//!
+//! 1. the number of characters from each class is sampled from its exact
+//!    distribution among compliant strings,
+//! 2. the class labels are uniformly shuffled into positions, and
+//! 3. each position is filled uniformly from its class.
+//!
+//! The same distribution gives the size of the constrained space, which
+//! `entropy::policy_entropy` reports in bits.
+//!
+//! A policy can also declare rules for human-chosen passwords, as in NIST SP
+//! 800-63B or a corporate standard: a minimum length, the longest run of one
+//! repeated character, banned substrings, banned wordlists and a minimum
+//! estimated entropy. `Policy::from_file` reads all of them from TOML or
+//! JSON, and `PolicyChecker` reports each configured rule as pass or fail.
+//! Generators hold `min_entropy` against their exact entropy and enforce the
+//! other rules by redrawing non-compliant items.
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+use rand::distributions::WeightedIndex;
+use rand::seq::SliceRandom;
+use rand::{CryptoRng, Rng, RngCore};
+use serde::{Deserialize, Serialize};
+
+use crate::charset::CharClass;
+use crate::entropy::{EstimateOptions, estimate_entropy_for_str_with};
+use crate::error::GenixError;
+use crate::generate::{Style, index_dist};
+use crate::matching::common_passwords;
+use crate::wordlist::{WordlistSource, load_wordlist};
+
+/// Name of the bundled common-password list in `banned_wordlists`.
+pub const COMMON_PASSWORDS: &str = "common-passwords";
+
+/// Character-class minimums and compliance rules for passwords.
+///
+/// The default policy has no requirements. In a policy file every field is
+/// optional and uses the names below, e.g. `min_length = 12` in TOML.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Policy {
+    /// Minimum number of `A-Z` characters.
+    pub min_upper: usize,
+    /// Minimum number of `a-z` characters.
+    pub min_lower: usize,
+    /// Minimum number of `0-9` characters.
+    pub min_digit: usize,
+    /// Minimum number of other (symbol) characters.
+    pub min_symbol: usize,
+    /// Minimum length in characters.
+    pub min_length: usize,
+    /// Longest allowed run of one repeated character.
+    pub max_repeated: Option<usize>,
+    /// Substrings that must not appear, compared case-insensitively.
+    pub banned_substrings: Vec<String>,
+    /// Lists of passwords that are refused outright (case-insensitively):
+    /// `common-passwords`, a built-in wordlist name or a file path.
+    pub banned_wordlists: Vec<String>,
+    /// Minimum estimated entropy in bits, using the `guesses` estimator.
+    pub min_entropy: Option<f64>,
+}
+
+impl Policy {
+    /// Read a policy from a `.json` file, or TOML otherwise.
+    ///
+    /// # Errors
+    /// Returns `GenixError::InputIo` if the file cannot be read and
+    /// `GenixError::InvalidPolicy` if it does not parse.
+    pub fn from_file(path: &str) -> Result<Self, GenixError> {
+        let text = fs::read_to_string(path).map_err(|source| GenixError::InputIo {
+            path: path.to_string(),
+            source,
+        })?;
+        let is_json = Path::new(path)
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("json"));
+        if is_json {
+            Policy::from_json(&text)
+        } else {
+            Policy::from_toml(&text)
+        }
+    }
+
+    /// Parse a TOML policy.
+    pub fn from_toml(text: &str) -> Result<Self, GenixError> {
+        toml::from_str(text).map_err(|e| GenixError::InvalidPolicy(e.to_string()))
+    }
+
+    /// Parse a JSON policy.
+    pub fn from_json(text: &str) -> Result<Self, GenixError> {
+        serde_json::from_str(text).map_err(|e| GenixError::InvalidPolicy(e.to_string()))
+    }
+
+    /// True when the policy requires nothing.
+    pub fn is_empty(&self) -> bool {
+        self.required_total() == 0 && !self.has_string_rules()
+    }
+
+    /// True when any rule beyond the class minimums is set.
+    pub fn has_string_rules(&self) -> bool {
+        self.min_length > 0
+            || self.max_repeated.is_some()
+            || !self.banned_substrings.is_empty()
+            || !self.banned_wordlists.is_empty()
+            || self.min_entropy.is_some()
+    }
+
+    /// Minimum count for `class`.
+    pub fn min_for(&self, class: CharClass) -> usize {
+        match class {
+            CharClass::Upper => self.min_upper,
+            CharClass::Lower => self.min_lower,
+            CharClass::Digit => self.min_digit,
+            CharClass::Symbol => self.min_symbol,
+        }
+    }
+
+    /// Sum of all minimum counts, i.e. the shortest length that can comply.
+    pub fn required_total(&self) -> usize {
+        CharClass::ALL.iter().map(|&c| self.min_for(c)).sum()
+    }
+
+    /// Return true if `s` meets every minimum count.
+    pub fn is_satisfied_by(&self, s: &str) -> bool {
+        CharClass::ALL.iter().all(|&class| {
+            s.chars().filter(|&c| CharClass::of(c) == class).count() >= self.min_for(class)
+        })
+    }
+
+    /// Check that a `length`-character password over `pool` can comply.
+    ///
+    /// # Errors
+    /// Returns `GenixError::PolicyUnsatisfiable` if the minimums exceed
+    /// `length` or a required class has no characters in `pool`.
+    pub fn validate(&self, pool: &[char], length: usize) -> Result<(), GenixError> {
+        if self.required_total() > length {
+            return Err(GenixError::PolicyUnsatisfiable(format!(
+                "policy requires {} characters but length is {}",
+                self.required_total(),
+                length
+            )));
+        }
+        for class in CharClass::ALL {
+            if self.min_for(class) > 0 && !pool.iter().any(|&c| CharClass::of(c) == class) {
+                return Err(GenixError::PolicyUnsatisfiable(format!(
+                    "policy requires {} characters but the pool has none",
+                    class
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Probability that a uniformly random `length`-character string over
+    /// `pool` meets the policy (1.0 for an empty policy).
+    pub fn compliant_fraction(&self, pool: &[char], length: usize) -> f64 {
+        ClassTable::new(self, pool, length).table[0][length]
+    }
+
+    /// Load the banned wordlists and return a reusable checker.
+    ///
+    /// # Errors
+    /// Returns the wordlist errors of `load_wordlist`, or
+    /// `GenixError::InvalidPolicy` for a `max_repeated` of zero.
+    pub fn checker(&self) -> Result<PolicyChecker, GenixError> {
+        if self.max_repeated == Some(0) {
+            return Err(GenixError::InvalidPolicy(
+                "max_repeated must be at least 1".into(),
+            ));
+        }
+        let banned_lists = self
+            .banned_wordlists
+            .iter()
+            .map(|name| {
+                let words: Vec<String> = if name == COMMON_PASSWORDS {
+                    common_passwords().map(str::to_string).collect()
+                } else {
+                    load_wordlist(Some(&WordlistSource::from(name.as_str())))?
+                };
+                let words = words.iter().map(|word| word.to_lowercase()).collect();
+                Ok((name.clone(), words))
+            })
+            .collect::<Result<_, GenixError>>()?;
+        Ok(PolicyChecker {
+            policy: self.clone(),
+            banned_lists,
+        })
+    }
+
+    /// Check `password` against every configured rule; see `PolicyChecker`.
+    pub fn check(
+        &self,
+        password: &str,
+        style: Style,
+        options: &EstimateOptions,
+    ) -> Result<ComplianceReport, GenixError> {
+        Ok(self.checker()?.check(password, style, options))
+    }
+}
+
+/// The outcome of one policy rule.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RuleOutcome {
+    /// The policy field, e.g. `min_length`.
+    pub rule: &'static str,
+    pub passed: bool,
+    /// What was measured against the requirement.
+    pub detail: String,
+}
+
+/// Pass/fail results for each configured rule of a policy.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ComplianceReport {
+    pub rules: Vec<RuleOutcome>,
+}
+
+impl ComplianceReport {
+    /// True when every rule passed.
+    pub fn passed(&self) -> bool {
+        self.rules.iter().all(|outcome| outcome.passed)
+    }
+
+    /// Names of the rules that failed.
+    pub fn failed_rules(&self) -> Vec<&'static str> {
+        self.rules
+            .iter()
+            .filter(|outcome| !outcome.passed)
+            .map(|outcome| outcome.rule)
+            .collect()
+    }
+}
+
+/// A policy with its banned wordlists loaded, for checking many passwords.
+#[derive(Debug, Clone)]
+pub struct PolicyChecker {
+    policy: Policy,
+    banned_lists: Vec<(String, HashSet<String>)>,
+}
+
+impl PolicyChecker {
+    /// The policy being enforced.
+    pub fn policy(&self) -> &Policy {
+        &self.policy
+    }
+
+    /// Evaluate every configured rule; rules left at their defaults are
+    /// skipped. Lengths and counts are in characters, and `min_entropy` is
+    /// scored like `estimate_entropy_for_str_with(password, style, options)`,
+    /// so it agrees with the estimate reported alongside it.
+    pub fn check(
+        &self,
+        password: &str,
+        style: Style,
+        options: &EstimateOptions,
+    ) -> ComplianceReport {
+        let policy = &self.policy;
+        let mut rules = Vec::new();
+        let mut rule = |rule: &'static str, passed: bool, detail: String| {
+            rules.push(RuleOutcome {
+                rule,
+                passed,
+                detail,
+            });
+        };
+
+        let length = password.chars().count();
+        if policy.min_length > 0 {
+            rule(
+                "min_length",
+                length >= policy.min_length,
+                format!("{} characters (minimum {})", length, policy.min_length),
+            );
+        }
+        for (name, class) in [
+            ("min_upper", CharClass::Upper),
+            ("min_lower", CharClass::Lower),
+            ("min_digit", CharClass::Digit),
+            ("min_symbol", CharClass::Symbol),
+        ] {
+            let min = policy.min_for(class);
+            if min > 0 {
+                let count = password
+                    .chars()
+                    .filter(|&c| CharClass::of(c) == class)
+                    .count();
+                rule(
+                    name,
+                    count >= min,
+                    format!("{} {} characters (minimum {})", count, class, min),
+                );
+            }
+        }
+        if let Some(max) = policy.max_repeated {
+            let run = longest_run(password);
+            rule(
+                "max_repeated",
+                run <= max,
+                format!("longest run of one character is {} (maximum {})", run, max),
+            );
+        }
+        if !policy.banned_substrings.is_empty() {
+            let lower = password.to_lowercase();
+            let found: Vec<String> = policy
+                .banned_substrings
+                .iter()
+                .filter(|banned| !banned.is_empty() && lower.contains(&banned.to_lowercase()))
+                .map(|banned| format!("{:?}", banned))
+                .collect();
+            let detail = if found.is_empty() {
+                "none found".to_string()
+            } else {
+                format!("contains {}", found.join(", "))
+            };
+            rule("banned_substrings", found.is_empty(), detail);
+        }
+        if !self.banned_lists.is_empty() {
+            let lower = password.to_lowercase();
+            let listed: Vec<&str> = self
+                .banned_lists
+                .iter()
+                .filter(|(_, words)| words.contains(&lower))
+                .map(|(name, _)| name.as_str())
+                .collect();
+            let detail = if listed.is_empty() {
+                "not listed".to_string()
+            } else {
+                format!("listed in {}", listed.join(", "))
+            };
+            rule("banned_wordlists", listed.is_empty(), detail);
+        }
+        if let Some(min) = policy.min_entropy {
+            match estimate_entropy_for_str_with(password, style, options) {
+                Ok(bits) => rule(
+                    "min_entropy",
+                    bits >= min,
+                    format!("{:.2} bits (minimum {})", bits, min),
+                ),
+                Err(e) => rule("min_entropy", false, format!("not estimated: {}", e)),
+            }
+        }
+        ComplianceReport { rules }
+    }
+}
+
+/// Length of the longest run of one repeated character.
+fn longest_run(s: &str) -> usize {
+    let mut longest = 0;
+    let mut run = 0;
+    let mut previous = None;
+    for c in s.chars() {
+        run = if previous == Some(c) { run + 1 } else { 1 };
+        previous = Some(c);
+        longest = longest.max(run);
+    }
+    longest
+}
+
+/// Pool split into classes plus the compliance table used for sampling.
+///
+/// `table[j][m]` is the probability that a uniform string of length `m`, drawn
+/// from the characters of classes `j..` only, meets the minimums of those
+/// classes. `table[CLASSES][m]` is 1 for `m == 0` and 0 otherwise.
+#[derive(Debug)]
+struct ClassTable {
+    classes: Vec<(Vec<char>, usize)>,
+    /// Share of the remaining pool taken by class `j`.
+    shares: Vec<f64>,
+    table: Vec<Vec<f64>>,
+    ln_fact: Vec<f64>,
+}
+
+impl ClassTable {
+    fn new(policy: &Policy, pool: &[char], length: usize) -> Self {
+        let classes: Vec<(Vec<char>, usize)> = CharClass::ALL
+            .iter()
+            .map(|&class| {
+                let chars = pool
+                    .iter()
+                    .copied()
+                    .filter(|&c| CharClass::of(c) == class)
+                    .collect();
+                (chars, policy.min_for(class))
+            })
+            .collect();
+
+        let mut remaining = pool.len();
+        let shares: Vec<f64> = classes
+            .iter()
+            .map(|(chars, _)| {
+                let share = if remaining == 0 {
+                    0.0
+                } else {
+                    chars.len() as f64 / remaining as f64
+                };
+                remaining -= chars.len();
+                share
+            })
+            .collect();
+
+        let mut ln_fact = vec![0.0f64; length + 1];
+        for i in 1..=length {
+            ln_fact[i] = ln_fact[i - 1] + (i as f64).ln();
+        }
+
+        let n = classes.len();
+        let mut table = vec![vec![0.0f64; length + 1]; n + 1];
+        table[n][0] = 1.0;
+        for j in (0..n).rev() {
+            for m in 0..=length {
+                table[j][m] = (classes[j].1..=m)
+                    .map(|k| binomial(&ln_fact, m, k, shares[j]) * table[j + 1][m - k])
+                    .sum();
+            }
+        }
+
+        ClassTable {
+            classes,
+            shares,
+            table,
+            ln_fact,
+        }
+    }
+}
+
+/// P(X = k) for X ~ Binomial(m, q), using the log-factorial table `ln_fact`.
+fn binomial(ln_fact: &[f64], m: usize, k: usize, q: f64) -> f64 {
+    if q <= 0.0 {
+        return if k == 0 { 1.0 } else { 0.0 };
+    }
+    if q >= 1.0 {
+        return if k == m { 1.0 } else { 0.0 };
+    }
+    let ln_choose = ln_fact[m] - ln_fact[k] - ln_fact[m - k];
+    (ln_choose + k as f64 * q.ln() + (m - k) as f64 * (1.0 - q).ln()).exp()
+}
+
+/// Draws policy-compliant strings uniformly at random.
+#[derive(Debug)]
+pub(crate) struct PolicySampler {
+    table: ClassTable,
+    length: usize,
+}
+
+impl PolicySampler {
+    /// Prepare a sampler; call `Policy::validate` first.
+    pub(crate) fn new(policy: &Policy, pool: &[char], length: usize) -> Self {
+        PolicySampler {
+            table: ClassTable::new(policy, pool, length),
+            length,
+        }
+    }
+
+    /// Sample one compliant string.
+    pub(crate) fn sample<R: RngCore + CryptoRng>(&self, rng: &mut R) -> String {
+        let table = &self.table;
+        let mut labels: Vec<usize> = Vec::with_capacity(self.length);
+        let mut remaining = self.length;
+        for (j, (_, min)) in table.classes.iter().enumerate() {
+            let weights: Vec<f64> = (0..=remaining)
+                .map(|k| {
+                    if k < *min {
+                        0.0
+                    } else {
+                        binomial(&table.ln_fact, remaining, k, table.shares[j])
+                            * table.table[j + 1][remaining - k]
+                    }
+                })
+                .collect();
+            let count = WeightedIndex::new(&weights)
+                .map(|dist| rng.sample(dist))
+                .unwrap_or(0);
+            labels.extend(std::iter::repeat_n(j, count));
+            remaining -= count;
+        }
+        labels.shuffle(rng);
+        labels
+            .into_iter()
+            .map(|j| {
+                let chars = &table.classes[j].0;
+                chars[rng.sample(index_dist(chars.len())) as usize]
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::charset::CharsetSpec;
+    use crate::entropy::Estimator;
+    use crate::generate::{GeneratorConfig, generate_many, seeded_rng};
+
+    fn policy() -> Policy {
+        Policy {
+            min_upper: 1,
+            min_digit: 2,
+            min_symbol: 1,
+            ..Policy::default()
+        }
+    }
+
+    #[test]
+    fn test_empty_policy_fraction_is_one() {
+        let pool = CharsetSpec::default().pool();
+        let fraction = Policy::default().compliant_fraction(&pool, 12);
+        assert!((fraction - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_fraction_matches_brute_force() {
+        // pool "aB1!" with length 4: count compliant strings exhaustively
+        let pool: Vec<char> = "aB1!".chars().collect();
+        let p = Policy {
+            min_digit: 2,
+            min_upper: 1,
+            ..Policy::default()
+        };
+        let mut compliant = 0;
+        for i in 0..256usize {
+            let s: String = (0..4).map(|d| pool[(i >> (2 * d)) & 3]).collect();
+            if p.is_satisfied_by(&s) {
+                compliant += 1;
+            }
+        }
+        let fraction = p.compliant_fraction(&pool, 4);
+        assert!((fraction - compliant as f64 / 256.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_sampler_always_complies() {
+        let pool = CharsetSpec::default().pool();
+        let sampler = PolicySampler::new(&policy(), &pool, 6);
+        let mut rng = seeded_rng("05").unwrap();
+        for _ in 0..200 {
+            let s = sampler.sample(&mut rng);
+            assert_eq!(s.chars().count(), 6);
+            assert!(policy().is_satisfied_by(&s), "{}", s);
+        }
+    }
+
+    #[test]
+    fn test_sampler_is_uniform_over_compliant_strings() {
+        // "aB1!" with length 3 and one digit required: 64 - 27 = 37 strings
+        let pool: Vec<char> = "aB1!".chars().collect();
+        let p = Policy {
+            min_digit: 1,
+            ..Policy::default()
+        };
+        let sampler = PolicySampler::new(&p, &pool, 3);
+        let mut rng = seeded_rng("07").unwrap();
+        let mut counts = std::collections::HashMap::new();
+        for _ in 0..37_000 {
+            *counts.entry(sampler.sample(&mut rng)).or_insert(0usize) += 1;
+        }
+        assert_eq!(counts.len(), 37);
+        assert!(
+            counts.values().all(|&n| (850..1150).contains(&n)),
+            "{:?}",
+            counts
+        );
+    }
+
+    #[test]
+    fn test_policy_file_and_compliance() {
+        let toml = r#"
+            min_length = 12
+            min_digit = 1
+            max_repeated = 2
+            banned_substrings = ["acme"]
+            banned_wordlists = ["common-passwords"]
+            min_entropy = 40
+        "#;
+        let policy = Policy::from_toml(toml).unwrap();
+        let json = r#"{"min_length": 12, "min_digit": 1, "max_repeated": 2,
+            "banned_substrings": ["acme"], "banned_wordlists": ["common-passwords"],
+            "min_entropy": 40}"#;
+        assert_eq!(Policy::from_json(json).unwrap(), policy);
+        assert!(matches!(
+            Policy::from_toml("min_lenght = 3"),
+            Err(GenixError::InvalidPolicy(_))
+        ));
+
+        let checker = policy.checker().unwrap();
+        let options = EstimateOptions {
+            estimator: Estimator::Guesses,
+            ..EstimateOptions::default()
+        };
+        let check = |password: &str| checker.check(password, Style::Random, &options);
+        let report = check("ACMEpaaassword");
+        assert!(!report.passed());
+        assert_eq!(
+            report.failed_rules(),
+            ["min_digit", "max_repeated", "banned_substrings"]
+        );
+        assert_eq!(report.rules.len(), 6);
+        assert_eq!(
+            check("password").failed_rules()[..2],
+            ["min_length", "min_digit"]
+        );
+        assert!(
+            check("password")
+                .failed_rules()
+                .contains(&"banned_wordlists")
+        );
+        assert!(check("vN4#qz8!Lr2@xk").passed());
+    }
+
+    #[test]
+    fn test_min_entropy_follows_style_and_options() {
+        let policy = Policy {
+            min_entropy: Some(30.0),
+            ..Policy::default()
+        };
+        let checker = policy.checker().unwrap();
+        let phrase = "apple-banana-cherry-delta";
+        let words = EstimateOptions {
+            wordlist: Some(WordlistSource::from("assets/eff_sample.txt")),
+            ..EstimateOptions::default()
+        };
+        let bits = estimate_entropy_for_str_with(phrase, Style::Passphrase, &words).unwrap();
+        let report = checker.check(phrase, Style::Passphrase, &words);
+        assert!(!report.passed());
+        assert_eq!(
+            report.rules[0].detail,
+            format!("{:.2} bits (minimum 30)", bits)
+        );
+        assert!(checker.check(phrase, Style::Random, &words).passed());
+
+        let options = EstimateOptions::default();
+        let token = &generate_many(&GeneratorConfig::new(Style::Token).length(16), 1).unwrap()[0];
+        let bits = estimate_entropy_for_str_with(token, Style::Token, &options).unwrap();
+        let report = checker.check(token, Style::Token, &options);
+        assert!(report.passed());
+        assert_eq!(
+            report.rules[0].detail,
+            format!("{:.2} bits (minimum 30)", bits)
+        );
+        let forged = checker.check("genix_notatoken", Style::Token, &options);
+        assert!(!forged.passed());
+    }
+
+    #[test]
+    fn test_longest_run() {
+        assert_eq!(longest_run(""), 0);
+        assert_eq!(longest_run("abc"), 1);
+        assert_eq!(longest_run("abbbcc"), 3);
+    }
+
+    #[test]
+    fn test_validate_rejects_impossible_policies() {
+        let digits: Vec<char> = "0123456789".chars().collect();
+        assert!(policy().validate(&digits, 10).is_err());
+        let pool = CharsetSpec::default().pool();
+        assert!(policy().validate(&pool, 3).is_err());
+        assert!(policy().validate(&pool, 4).is_ok());
+    }
+}
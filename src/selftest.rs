@@ -0,0 +1,591 @@
+//! Statistical self-test of the generators.
+//!
+//! `selftest` generates a large sample of every style, decodes each item back
+//! into the uniform choices it was built from (pool indices, word indices,
+//! the random nibbles of a UUID, ...) and runs three tests on every stream of
+//! choices:
+//!
+//! - **frequency**: chi-squared goodness of fit against the uniform
+//!   distribution over the expected alphabet;
+//! - **serial correlation**: lag-1 autocorrelation of the values, which is
+//!   approximately `N(0, 1/n)` for independent draws;
+//! - **runs**: Wald-Wolfowitz runs test on the values split at half the
+//!   alphabet.
+//!
+//! The `random` style is also sampled under `selftest_policy`, whose output is
+//! uniform within each character class, so each class is its own stream.
+//!
+//! A sample too small for 5 observations per symbol in every stream is
+//! refused, since the frequency test would be meaningless. Any character
+//! outside the expected alphabet fails the stream outright.
+//! A deviation is significant when its p-value falls below `alpha` divided by
+//! the number of tests run (Bonferroni correction), so the whole run has a
+//! false-alarm rate of at most `alpha`.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use rand::{CryptoRng, RngCore, thread_rng};
+
+use crate::bip39;
+use crate::charset::{CharClass, CharsetSpec};
+use crate::error::GenixError;
+use crate::generate::{Base64Encoding, GeneratorConfig, LengthUnit, Style};
+use crate::identifier::NANOID_ALPHABET;
+use crate::passphrase::PassphraseFormat;
+use crate::pattern::{Pattern, Token};
+use crate::policy::Policy;
+use crate::token;
+use crate::wordlist::load_wordlist;
+
+/// Template used for the `pattern` style: one of every class placeholder.
+pub const SELFTEST_PATTERN: &str = "aAcCvV9hHxXs*";
+
+/// Policy the `random` style is additionally tested under.
+pub fn selftest_policy() -> Policy {
+    Policy {
+        min_upper: 1,
+        min_lower: 1,
+        min_digit: 2,
+        min_symbol: 1,
+        ..Policy::default()
+    }
+}
+
+const HEX: &str = "0123456789abcdef";
+const BASE32: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+const BASE64_URL: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+const CROCKFORD: &str = "0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+
+/// Sample size, significance level and styles for `selftest`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SelftestOptions {
+    /// Items generated per style.
+    pub samples: usize,
+    /// Family-wise significance level across all tests.
+    pub alpha: f64,
+    /// Styles to test; empty means every style.
+    pub styles: Vec<Style>,
+}
+
+impl Default for SelftestOptions {
+    fn default() -> Self {
+        SelftestOptions {
+            samples: 10_000,
+            alpha: 0.001,
+            styles: Vec::new(),
+        }
+    }
+}
+
+/// Result of one statistical test.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TestOutcome {
+    /// `frequency`, `serial` or `runs`.
+    pub name: &'static str,
+    /// The test statistic (chi-squared, or a z-score for the other two).
+    pub statistic: f64,
+    /// Probability of a deviation at least this large under uniformity.
+    pub p_value: f64,
+}
+
+/// Tests run on one stream of uniform choices.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StreamReport {
+    pub style: Style,
+    /// Which choices of the style this stream holds, e.g. `chars` or `words`.
+    pub label: String,
+    /// Number of equally likely symbols.
+    pub alphabet: usize,
+    pub observations: usize,
+    /// Observed symbols that are not in the alphabet.
+    pub unexpected: usize,
+    pub tests: Vec<TestOutcome>,
+}
+
+/// Everything `selftest` measured.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SelftestReport {
+    pub streams: Vec<StreamReport>,
+    /// Per-test p-value below which a deviation is significant.
+    pub threshold: f64,
+}
+
+impl SelftestReport {
+    /// True if a stream has unexpected symbols or a significant deviation.
+    pub fn stream_failed(&self, stream: &StreamReport) -> bool {
+        stream.unexpected > 0 || stream.tests.iter().any(|t| t.p_value < self.threshold)
+    }
+
+    /// True if no stream failed.
+    pub fn passed(&self) -> bool {
+        !self.streams.iter().any(|s| self.stream_failed(s))
+    }
+}
+
+/// Run the self-test with the thread-local RNG.
+///
+/// # Errors
+/// Returns `GenixError` if a style cannot be built (for example, an unreadable
+/// wordlist), or `GenixError::TooFewSamples` if `samples` gives some stream
+/// fewer than 5 observations per symbol.
+pub fn selftest(options: &SelftestOptions) -> Result<SelftestReport, GenixError> {
+    selftest_with_rng(options, &mut thread_rng())
+}
+
+/// Like `selftest`, drawing randomness from `rng`.
+pub fn selftest_with_rng<R: RngCore + CryptoRng>(
+    options: &SelftestOptions,
+    rng: &mut R,
+) -> Result<SelftestReport, GenixError> {
+    let styles = if options.styles.is_empty() {
+        Style::ALL.to_vec()
+    } else {
+        options.styles.clone()
+    };
+    let mut streams = Vec::new();
+    for style in styles {
+        streams.extend(style_streams(style, options.samples, rng)?);
+    }
+    // streams with unexpected symbols fail anyway, whatever their size
+    if let Some(small) = streams
+        .iter()
+        .find(|s| s.observations + s.unexpected < 5 * s.alphabet)
+    {
+        return Err(GenixError::TooFewSamples {
+            stream: format!("{} {}", small.style, small.label),
+            observations: small.observations + small.unexpected,
+            required: 5 * small.alphabet,
+        });
+    }
+    let total_tests = streams.iter().map(|s| s.tests.len()).sum::<usize>().max(1);
+    Ok(SelftestReport {
+        streams,
+        threshold: options.alpha / total_tests as f64,
+    })
+}
+
+/// Generate `samples` items of `style` and test each stream of choices.
+fn style_streams<R: RngCore + CryptoRng>(
+    style: Style,
+    samples: usize,
+    rng: &mut R,
+) -> Result<Vec<StreamReport>, GenixError> {
+    let config = GeneratorConfig::new(style)
+        .encoding(Base64Encoding::UrlNoPad)
+        .padding(false)
+        .length_unit(LengthUnit::Chars)
+        .pattern(SELFTEST_PATTERN)
+        .passphrase_format(PassphraseFormat {
+            // EFF words may contain '-', never ' '
+            separator: " ".into(),
+            ..PassphraseFormat::default()
+        });
+    let mut generator = config.build_with_rng(&mut *rng)?;
+    let items: Vec<String> = generator
+        .generate_iter()
+        .take(samples)
+        .collect::<Result<_, _>>()?;
+    let chars = |alphabet: &str, pick: &dyn Fn(&str) -> Vec<char>| {
+        let alphabet: Vec<char> = alphabet.chars().collect();
+        let symbols = items.iter().flat_map(|item| pick(item));
+        stream(style, "chars", &alphabet, symbols)
+    };
+    let all = |item: &str| item.chars().collect::<Vec<char>>();
+
+    let streams = match style {
+        Style::Random => {
+            let pool: String = CharsetSpec::default().pool().into_iter().collect();
+            let mut streams = vec![chars(&pool, &all)];
+            streams.extend(policy_streams(samples, rng)?);
+            streams
+        }
+        Style::Pin => vec![chars("0123456789", &all)],
+        Style::Hex => vec![chars(HEX, &all)],
+        Style::Base64 => vec![chars(BASE64_URL, &all)],
+        Style::Base32 => vec![chars(BASE32, &all)],
+        Style::Nanoid => vec![chars(NANOID_ALPHABET, &all)],
+        Style::Token => vec![chars(token::BASE62, &|item| {
+            let body = item.rsplit_once('_').map_or("", |(_, body)| body);
+            let random = body.len().saturating_sub(token::CHECKSUM_LEN);
+            body[..random].chars().collect()
+        })],
+        // skip the version nibble and the variant nibble (only 2 random bits)
+        Style::Uuid4 => vec![chars(HEX, &|item| {
+            uuid_nibbles(item)
+                .enumerate()
+                .filter(|(i, _)| *i != 12 && *i != 16)
+                .map(|(_, c)| c)
+                .collect()
+        })],
+        // rand_a and rand_b, after the 48-bit timestamp
+        Style::Uuid7 => vec![chars(HEX, &|item| {
+            uuid_nibbles(item)
+                .enumerate()
+                .filter(|(i, _)| (13..16).contains(i) || *i > 16)
+                .map(|(_, c)| c)
+                .collect()
+        })],
+        // the last 16 characters are exactly the 80 random bits
+        Style::Ulid => vec![chars(CROCKFORD, &|item| item.chars().skip(10).collect())],
+        Style::Passphrase => {
+            let words = load_wordlist(None)?;
+            let symbols = items.iter().flat_map(|item| item.split(' '));
+            vec![stream(style, "words", &words, symbols.map(String::from))]
+        }
+        // the last word carries checksum bits
+        Style::Bip39 => {
+            let words = bip39::english_words();
+            let symbols = items.iter().flat_map(|item| {
+                let phrase: Vec<&str> = item.split(' ').collect();
+                phrase[..phrase.len() - 1].to_vec()
+            });
+            vec![stream(style, "words", &words, symbols)]
+        }
+        Style::Pattern | Style::Pronounceable => {
+            let pattern = match style {
+                Style::Pattern => Pattern::parse(SELFTEST_PATTERN)?,
+                _ => Pattern::pronounceable(style.default_length()),
+            };
+            class_streams(style, &pattern, &items)
+        }
+    };
+    Ok(streams)
+}
+
+/// Generate `samples` `random` items under `selftest_policy`, one stream per
+/// character class: the policy skews how many characters each class gets,
+/// but not which character of the class is drawn.
+fn policy_streams<R: RngCore + CryptoRng>(
+    samples: usize,
+    rng: &mut R,
+) -> Result<Vec<StreamReport>, GenixError> {
+    let mut generator = GeneratorConfig::new(Style::Random)
+        .policy(selftest_policy())
+        .build_with_rng(&mut *rng)?;
+    let items: Vec<String> = generator
+        .generate_iter()
+        .take(samples)
+        .collect::<Result<_, _>>()?;
+    let pool = CharsetSpec::default().pool();
+    Ok(CharClass::ALL
+        .iter()
+        .map(|&class| {
+            let alphabet: Vec<char> = pool
+                .iter()
+                .copied()
+                .filter(|&c| CharClass::of(c) == class)
+                .collect();
+            let symbols = items
+                .iter()
+                .flat_map(|item| item.chars())
+                .filter(|&c| CharClass::of(c) == class);
+            let label = format!("policy {}", class);
+            stream(Style::Random, &label, &alphabet, symbols)
+        })
+        .collect())
+}
+
+/// One stream per distinct character class of `pattern`.
+fn class_streams(style: Style, pattern: &Pattern, items: &[String]) -> Vec<StreamReport> {
+    let mut classes: Vec<(&[char], Vec<char>)> = Vec::new();
+    for item in items {
+        for (token, c) in pattern.tokens().iter().zip(item.chars()) {
+            if let Token::Class(set) = token {
+                match classes
+                    .iter_mut()
+                    .find(|(known, _)| *known == set.as_slice())
+                {
+                    Some((_, seen)) => seen.push(c),
+                    None => classes.push((set, vec![c])),
+                }
+            }
+        }
+    }
+    classes
+        .into_iter()
+        .map(|(set, seen)| {
+            let head: String = set.iter().take(3).collect();
+            let label = format!("class {}..{}", head, set[set.len() - 1]);
+            stream(style, &label, set, seen)
+        })
+        .collect()
+}
+
+/// The 32 hex digits of a canonical UUID.
+fn uuid_nibbles(item: &str) -> impl Iterator<Item = char> + '_ {
+    item.chars().filter(|c| *c != '-')
+}
+
+/// Map `symbols` to their indices in `alphabet` and run every test.
+fn stream<T: Eq + Hash>(
+    style: Style,
+    label: &str,
+    alphabet: &[T],
+    symbols: impl IntoIterator<Item = T>,
+) -> StreamReport {
+    // duplicate alphabet entries share one index, so values cover 0..size
+    let mut index: HashMap<&T, usize> = HashMap::with_capacity(alphabet.len());
+    for symbol in alphabet {
+        let next = index.len();
+        index.entry(symbol).or_insert(next);
+    }
+    let size = index.len();
+    let mut values = Vec::new();
+    let mut unexpected = 0;
+    for symbol in symbols {
+        match index.get(&symbol) {
+            Some(&i) => values.push(i),
+            None => unexpected += 1,
+        }
+    }
+    let mut tests = Vec::new();
+    if values.len() >= 5 * size {
+        let mut counts = vec![0u64; size];
+        for &v in &values {
+            counts[v] += 1;
+        }
+        tests.push(chi_squared_uniform(&counts));
+    }
+    if values.len() > 2 {
+        tests.push(serial_correlation(&values));
+        tests.push(runs_test(&values, size));
+    }
+    StreamReport {
+        style,
+        label: label.to_string(),
+        alphabet: size,
+        observations: values.len(),
+        unexpected,
+        tests,
+    }
+}
+
+/// Chi-squared goodness of fit of `counts` against the uniform distribution.
+pub fn chi_squared_uniform(counts: &[u64]) -> TestOutcome {
+    let total: u64 = counts.iter().sum();
+    let expected = total as f64 / counts.len() as f64;
+    let statistic: f64 = counts
+        .iter()
+        .map(|&c| (c as f64 - expected).powi(2) / expected)
+        .sum();
+    let df = (counts.len() - 1).max(1) as f64;
+    TestOutcome {
+        name: "frequency",
+        statistic,
+        p_value: gamma_q(df / 2.0, statistic / 2.0),
+    }
+}
+
+/// Lag-1 serial correlation of `values`, as a z-score with a two-sided p-value.
+pub fn serial_correlation(values: &[usize]) -> TestOutcome {
+    let n = values.len() as f64;
+    let mean = values.iter().map(|&v| v as f64).sum::<f64>() / n;
+    let centred: Vec<f64> = values.iter().map(|&v| v as f64 - mean).collect();
+    let variance: f64 = centred.iter().map(|d| d * d).sum();
+    let covariance: f64 = centred.windows(2).map(|w| w[0] * w[1]).sum();
+    let r = if variance > 0.0 {
+        covariance / variance
+    } else {
+        1.0
+    };
+    normal_outcome("serial", r * n.sqrt())
+}
+
+/// Wald-Wolfowitz runs test of `values` split into `< alphabet / 2` and the rest.
+pub fn runs_test(values: &[usize], alphabet: usize) -> TestOutcome {
+    let split = alphabet / 2;
+    let low = values.iter().filter(|&&v| v < split).count() as f64;
+    let n = values.len() as f64;
+    let high = n - low;
+    if low == 0.0 || high == 0.0 {
+        return normal_outcome("runs", f64::INFINITY);
+    }
+    let runs = 1 + values
+        .windows(2)
+        .filter(|w| (w[0] < split) != (w[1] < split))
+        .count();
+    let mean = 2.0 * low * high / n + 1.0;
+    let variance = (mean - 1.0) * (mean - 2.0) / (n - 1.0);
+    normal_outcome("runs", (runs as f64 - mean) / variance.sqrt())
+}
+
+/// A two-sided standard-normal outcome for the z-score `z`.
+fn normal_outcome(name: &'static str, z: f64) -> TestOutcome {
+    let p_value = if z.is_finite() {
+        erfc(z.abs() / std::f64::consts::SQRT_2)
+    } else {
+        0.0
+    };
+    TestOutcome {
+        name,
+        statistic: z,
+        p_value,
+    }
+}
+
+/// Complementary error function, via `erfc(x) = Q(1/2, x^2)` for `x >= 0`.
+fn erfc(x: f64) -> f64 {
+    gamma_q(0.5, x * x)
+}
+
+/// Regularized upper incomplete gamma function `Q(a, x)`.
+fn gamma_q(a: f64, x: f64) -> f64 {
+    if x <= 0.0 {
+        return 1.0;
+    }
+    let prefix = (a * x.ln() - x - ln_gamma(a)).exp();
+    if x < a + 1.0 {
+        // series for P(a, x)
+        let mut term = 1.0 / a;
+        let mut sum = term;
+        let mut n = a;
+        for _ in 0..1000 {
+            n += 1.0;
+            term *= x / n;
+            sum += term;
+            if term.abs() < sum.abs() * 1e-15 {
+                break;
+            }
+        }
+        (1.0 - sum * prefix).max(0.0)
+    } else {
+        // Lentz's continued fraction for Q(a, x)
+        let tiny = 1e-300;
+        let mut b = x + 1.0 - a;
+        let mut c = 1.0 / tiny;
+        let mut d = 1.0 / b;
+        let mut h = d;
+        for i in 1..1000 {
+            let an = -(i as f64) * (i as f64 - a);
+            b += 2.0;
+            d = an * d + b;
+            if d.abs() < tiny {
+                d = tiny;
+            }
+            c = b + an / c;
+            if c.abs() < tiny {
+                c = tiny;
+            }
+            d = 1.0 / d;
+            let delta = d * c;
+            h *= delta;
+            if (delta - 1.0).abs() < 1e-15 {
+                break;
+            }
+        }
+        (prefix * h).min(1.0)
+    }
+}
+
+/// Natural log of the gamma function (Lanczos approximation, g = 7).
+fn ln_gamma(x: f64) -> f64 {
+    const COEFFICIENTS: [f64; 9] = [
+        0.999_999_999_999_809_9,
+        676.520_368_121_885_1,
+        -1_259.139_216_722_402_8,
+        771.323_428_777_653_1,
+        -176.615_029_162_140_6,
+        12.507_343_278_686_905,
+        -0.138_571_095_265_720_12,
+        9.984_369_578_019_572e-6,
+        1.505_632_735_149_311_6e-7,
+    ];
+    if x < 0.5 {
+        let pi = std::f64::consts::PI;
+        return (pi / (pi * x).sin()).ln() - ln_gamma(1.0 - x);
+    }
+    let x = x - 1.0;
+    let t = x + 7.5;
+    let sum = COEFFICIENTS[1..]
+        .iter()
+        .enumerate()
+        .fold(COEFFICIENTS[0], |acc, (i, c)| {
+            acc + c / (x + i as f64 + 1.0)
+        });
+    0.5 * (2.0 * std::f64::consts::PI).ln() + (x + 0.5) * t.ln() - t + sum.ln()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate::seeded_rng;
+
+    #[test]
+    fn test_distribution_functions() {
+        // chi-squared with 2 degrees of freedom: p = exp(-x / 2)
+        assert!((gamma_q(1.0, 1.0) - (-1.0f64).exp()).abs() < 1e-12);
+        assert!((erfc(1.0) - 0.157_299_207_050_285_1).abs() < 1e-12);
+        assert!((ln_gamma(5.0) - 24f64.ln()).abs() < 1e-12);
+        let fair = chi_squared_uniform(&[100, 100, 100, 100]);
+        assert_eq!(fair.statistic, 0.0);
+        assert!((fair.p_value - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_detects_modulo_bias() {
+        let mut rng = seeded_rng("18").unwrap();
+        let mut bytes = vec![0u8; 50_000];
+        rng.fill_bytes(&mut bytes);
+        // byte % 200 makes 0..56 twice as likely as the rest
+        let biased: Vec<usize> = bytes.iter().map(|&b| b as usize % 200).collect();
+        let report = stream(
+            Style::Random,
+            "biased",
+            &(0..200).collect::<Vec<_>>(),
+            biased,
+        );
+        assert!(report.tests.iter().any(|t| t.p_value < 1e-6));
+        let sorted: Vec<usize> = (0..20_000).map(|i| i % 10).collect();
+        assert!(serial_correlation(&sorted).p_value < 1e-6);
+        assert!(runs_test(&sorted, 10).p_value < 1e-6);
+    }
+
+    #[test]
+    fn test_every_style_passes() {
+        // 20-word passphrases need 7776 * 5 / 20 = 1944 items
+        let options = SelftestOptions {
+            samples: 2_000,
+            ..SelftestOptions::default()
+        };
+        let report = selftest_with_rng(&options, &mut seeded_rng("18").unwrap()).unwrap();
+        for style in Style::ALL {
+            assert!(report.streams.iter().any(|s| s.style == style), "{}", style);
+        }
+        let policy = report
+            .streams
+            .iter()
+            .filter(|s| s.label.starts_with("policy"));
+        assert_eq!(policy.count(), 4);
+        for stream in &report.streams {
+            assert_eq!(stream.unexpected, 0, "{} {}", stream.style, stream.label);
+            assert!(!report.stream_failed(stream), "{:?}", stream);
+        }
+        assert!(report.passed());
+    }
+
+    #[test]
+    fn test_refuses_too_few_samples() {
+        // a 20-digit pin gives 20 observations per item, 50 are needed
+        let options = |samples| SelftestOptions {
+            samples,
+            styles: vec![Style::Pin],
+            ..SelftestOptions::default()
+        };
+        for samples in [0, 1, 2] {
+            let err = selftest_with_rng(&options(samples), &mut seeded_rng("18").unwrap());
+            assert!(
+                matches!(err, Err(GenixError::TooFewSamples { required: 50, .. })),
+                "{}",
+                samples
+            );
+        }
+        let report = selftest_with_rng(&options(3), &mut seeded_rng("18").unwrap()).unwrap();
+        assert!(
+            report.streams[0]
+                .tests
+                .iter()
+                .any(|t| t.name == "frequency")
+        );
+    }
+}
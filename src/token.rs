@@ -0,0 +1,123 @@
+//! Prefixed, self-validating API tokens.
+//!
+//! A token looks like `genix_4Qx0...Zb1w3Xa`: a prefix that secret scanners
+//! can grep for, an underscore, `length` random base62 characters, and a
+//! 6-character base62 CRC32 checksum. The checksum covers the prefix, the
+//! underscore and the random part, so `validate_token` catches typos and
+//! truncation offline. It is not a MAC: anyone can mint a token that passes.
+
+use rand::{CryptoRng, Rng, RngCore};
+
+use crate::error::GenixError;
+use crate::generate::index_dist;
+
+/// Base62 alphabet used for the random part and the checksum.
+pub const BASE62: &str = "0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+/// Prefix used when none is configured.
+pub const DEFAULT_PREFIX: &str = "genix";
+/// Characters in the encoded checksum (62^6 > 2^32).
+pub const CHECKSUM_LEN: usize = 6;
+
+/// Check that `prefix` is non-empty ASCII alphanumerics or underscores.
+///
+/// # Errors
+/// Returns `GenixError::InvalidToken` otherwise.
+pub fn validate_prefix(prefix: &str) -> Result<(), GenixError> {
+    if prefix.is_empty()
+        || !prefix
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_')
+    {
+        return Err(GenixError::InvalidToken(format!(
+            "prefix {:?} must be non-empty letters, digits or '_'",
+            prefix
+        )));
+    }
+    Ok(())
+}
+
+/// Generate a token with `length` random base62 characters after `prefix_`.
+/// Call `validate_prefix` first.
+pub fn generate_token<R: RngCore + CryptoRng>(rng: &mut R, prefix: &str, length: usize) -> String {
+    let alphabet = BASE62.as_bytes();
+    let dist = index_dist(alphabet.len());
+    let mut token = format!("{}_", prefix);
+    token.extend((0..length).map(|_| char::from(alphabet[rng.sample(dist) as usize])));
+    let checksum = encode_checksum(crc32fast::hash(token.as_bytes()));
+    token.push_str(&checksum);
+    token
+}
+
+/// Verify the checksum of `token` and return the number of random bits in
+/// its body (`random chars * log2(62)`).
+///
+/// # Errors
+/// Returns `GenixError::InvalidToken` if there is no `prefix_`, the body is
+/// not base62 or shorter than the checksum, or the checksum does not match.
+pub fn validate_token(token: &str) -> Result<f64, GenixError> {
+    let (prefix, body) = token
+        .rsplit_once('_')
+        .ok_or_else(|| GenixError::InvalidToken("missing '<prefix>_'".into()))?;
+    validate_prefix(prefix)?;
+    if body.len() <= CHECKSUM_LEN || !body.bytes().all(|b| b.is_ascii_alphanumeric()) {
+        return Err(GenixError::InvalidToken(
+            "body must be base62 and longer than the checksum".into(),
+        ));
+    }
+    let (signed, checksum) = token.split_at(token.len() - CHECKSUM_LEN);
+    if encode_checksum(crc32fast::hash(signed.as_bytes())) != checksum {
+        return Err(GenixError::InvalidToken("checksum mismatch".into()));
+    }
+    let random_chars = body.len() - CHECKSUM_LEN;
+    Ok(random_chars as f64 * 62f64.log2())
+}
+
+/// Fixed-width base62 encoding of a CRC32 value, most significant digit first.
+fn encode_checksum(mut crc: u32) -> String {
+    let alphabet = BASE62.as_bytes();
+    let mut digits = [b'0'; CHECKSUM_LEN];
+    for digit in digits.iter_mut().rev() {
+        *digit = alphabet[(crc % 62) as usize];
+        crc /= 62;
+    }
+    String::from_utf8_lossy(&digits).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate::seeded_rng;
+
+    #[test]
+    fn test_generated_tokens_validate() {
+        let mut rng = seeded_rng("15").unwrap();
+        let token = generate_token(&mut rng, "svc_live", 30);
+        assert!(token.starts_with("svc_live_"));
+        assert_eq!(token.len(), "svc_live_".len() + 30 + CHECKSUM_LEN);
+        let bits = validate_token(&token).unwrap();
+        assert!((bits - 30.0 * 62f64.log2()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_typos_and_truncation_are_rejected() {
+        let mut rng = seeded_rng("15").unwrap();
+        let token = generate_token(&mut rng, "genix", 30);
+        let mut typo = token.clone().into_bytes();
+        typo[10] = if typo[10] == b'a' { b'b' } else { b'a' };
+        let typo = String::from_utf8(typo).unwrap();
+        for bad in [&typo, &token[..token.len() - 1], "genix", "_abcdefghij"] {
+            assert!(
+                matches!(validate_token(bad), Err(GenixError::InvalidToken(_))),
+                "{}",
+                bad
+            );
+        }
+    }
+
+    #[test]
+    fn test_checksum_encoding_is_fixed_width() {
+        assert_eq!(encode_checksum(0), "000000");
+        assert_eq!(encode_checksum(61), "00000z");
+        assert_eq!(encode_checksum(u32::MAX).len(), CHECKSUM_LEN);
+    }
+}
@@ -0,0 +1,210 @@
+//! TOTP secrets, `otpauth://` URIs and one-time codes.
+//!
+//! Secrets are random bytes written in RFC 4648 base32, the form
+//! authenticator apps expect. Codes follow RFC 6238 with HMAC-SHA1, the
+//! algorithm every authenticator supports: the Unix time is divided into
+//! `period`-second steps and the step number is fed to RFC 4226 HOTP.
+//! `otpauth_uri` builds the Key URI that apps import from a QR code.
+
+use base32::Alphabet;
+use hmac::{Hmac, Mac};
+use sha1::Sha1;
+
+use crate::error::GenixError;
+
+/// Secret size recommended by RFC 4226 (160 bits, the HMAC-SHA1 block output).
+pub const SECRET_BYTES: usize = 20;
+/// Smallest secret RFC 4226 allows (128 bits).
+pub const MIN_SECRET_BYTES: usize = 16;
+/// Code length used by authenticator apps unless told otherwise.
+pub const DEFAULT_DIGITS: u32 = 6;
+/// Time step in seconds used by authenticator apps unless told otherwise.
+pub const DEFAULT_PERIOD: u64 = 30;
+
+/// Encode `bytes` as upper-case RFC 4648 base32, with or without `=` padding.
+pub fn encode_base32(bytes: &[u8], padding: bool) -> String {
+    base32::encode(Alphabet::Rfc4648 { padding }, bytes)
+}
+
+/// Check the size of a new secret.
+///
+/// # Errors
+/// Returns `GenixError::InvalidTotp` below `MIN_SECRET_BYTES`.
+pub fn validate_secret_bytes(bytes: usize) -> Result<(), GenixError> {
+    if bytes < MIN_SECRET_BYTES {
+        return Err(GenixError::InvalidTotp(format!(
+            "secrets need at least {} bytes, not {}",
+            MIN_SECRET_BYTES, bytes
+        )));
+    }
+    Ok(())
+}
+
+/// Decode a base32 secret as typed by a person: case, spaces, dashes and
+/// trailing `=` padding are ignored.
+///
+/// # Errors
+/// Returns `GenixError::InvalidTotp` if the secret is empty or not base32.
+pub fn decode_secret(secret: &str) -> Result<Vec<u8>, GenixError> {
+    let cleaned: String = secret
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '-')
+        .map(|c| c.to_ascii_uppercase())
+        .collect();
+    let cleaned = cleaned.trim_end_matches('=');
+    if cleaned.is_empty() {
+        return Err(GenixError::InvalidTotp("secret is empty".into()));
+    }
+    base32::decode(Alphabet::Rfc4648 { padding: false }, cleaned)
+        .ok_or_else(|| GenixError::InvalidTotp("secret is not RFC 4648 base32".into()))
+}
+
+/// RFC 4226 HOTP code for `counter`, zero-padded to `digits` digits.
+pub fn hotp(key: &[u8], counter: u64, digits: u32) -> String {
+    let mut mac = Hmac::<Sha1>::new_from_slice(key).expect("HMAC accepts any key length");
+    mac.update(&counter.to_be_bytes());
+    let hash = mac.finalize().into_bytes();
+    // dynamic truncation: the low nibble of the last byte picks 4 bytes
+    let offset = usize::from(hash[hash.len() - 1] & 0x0f);
+    let value = u32::from_be_bytes([
+        hash[offset] & 0x7f,
+        hash[offset + 1],
+        hash[offset + 2],
+        hash[offset + 3],
+    ]);
+    format!(
+        "{:0width$}",
+        value % 10u32.pow(digits),
+        width = digits as usize
+    )
+}
+
+/// RFC 6238 TOTP code for the Unix time `unix_secs`.
+///
+/// # Errors
+/// Returns `GenixError::InvalidTotp` unless `digits` is 6 to 8 and `period`
+/// is positive.
+pub fn totp(key: &[u8], unix_secs: u64, period: u64, digits: u32) -> Result<String, GenixError> {
+    validate_parameters(digits, period)?;
+    Ok(hotp(key, unix_secs / period, digits))
+}
+
+/// Seconds until the code for `unix_secs` expires.
+pub fn seconds_remaining(unix_secs: u64, period: u64) -> u64 {
+    period - unix_secs % period
+}
+
+/// Build an `otpauth://totp/Issuer:account?...` Key URI for `secret` (base32).
+///
+/// The label and query values are percent-encoded, and the secret is written
+/// without padding as the Key URI format requires.
+///
+/// # Errors
+/// Returns `GenixError::InvalidTotp` if `issuer` or `account` is empty or
+/// contains `:` (the label separator), or the parameters are out of range.
+pub fn otpauth_uri(
+    secret: &str,
+    issuer: &str,
+    account: &str,
+    digits: u32,
+    period: u64,
+) -> Result<String, GenixError> {
+    validate_parameters(digits, period)?;
+    for (name, value) in [("issuer", issuer), ("account", account)] {
+        if value.is_empty() || value.contains(':') {
+            return Err(GenixError::InvalidTotp(format!(
+                "{} must be non-empty and must not contain ':'",
+                name
+            )));
+        }
+    }
+    Ok(format!(
+        "otpauth://totp/{}:{}?secret={}&issuer={}&algorithm=SHA1&digits={}&period={}",
+        percent_encode(issuer),
+        percent_encode(account),
+        secret.trim_end_matches('='),
+        percent_encode(issuer),
+        digits,
+        period
+    ))
+}
+
+/// Check the code length and time step shared by `totp` and `otpauth_uri`.
+fn validate_parameters(digits: u32, period: u64) -> Result<(), GenixError> {
+    if !(6..=8).contains(&digits) {
+        return Err(GenixError::InvalidTotp(format!(
+            "codes have 6 to 8 digits, not {}",
+            digits
+        )));
+    }
+    if period == 0 {
+        return Err(GenixError::InvalidTotp("period must be positive".into()));
+    }
+    Ok(())
+}
+
+/// Percent-encode everything except RFC 3986 unreserved characters.
+fn percent_encode(s: &str) -> String {
+    s.bytes()
+        .map(|b| {
+            if b.is_ascii_alphanumeric() || b"-._~".contains(&b) {
+                char::from(b).to_string()
+            } else {
+                format!("%{:02X}", b)
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RFC_KEY: &[u8] = b"12345678901234567890";
+
+    #[test]
+    fn test_base32_round_trip() {
+        // from the RFC 4648 test vectors
+        assert_eq!(encode_base32(b"foobar", true), "MZXW6YTBOI======");
+        assert_eq!(encode_base32(b"foobar", false), "MZXW6YTBOI");
+        assert_eq!(decode_secret("mzxw 6ytb oi======").unwrap(), b"foobar");
+        assert!(matches!(
+            decode_secret("MZXW1"),
+            Err(GenixError::InvalidTotp(_))
+        ));
+        assert!(decode_secret(" = ").is_err());
+    }
+
+    #[test]
+    fn test_secret_size_limit() {
+        assert!(validate_secret_bytes(MIN_SECRET_BYTES).is_ok());
+        assert!(matches!(
+            validate_secret_bytes(MIN_SECRET_BYTES - 1),
+            Err(GenixError::InvalidTotp(_))
+        ));
+        assert!(validate_secret_bytes(0).is_err());
+    }
+
+    #[test]
+    fn test_reference_codes() {
+        // RFC 4226 appendix D and RFC 6238 appendix B (SHA-1)
+        assert_eq!(hotp(RFC_KEY, 0, 6), "755224");
+        assert_eq!(hotp(RFC_KEY, 9, 6), "520489");
+        assert_eq!(totp(RFC_KEY, 59, 30, 8).unwrap(), "94287082");
+        assert_eq!(totp(RFC_KEY, 1_111_111_109, 30, 8).unwrap(), "07081804");
+        assert_eq!(seconds_remaining(59, 30), 1);
+        assert!(totp(RFC_KEY, 59, 0, 6).is_err());
+        assert!(totp(RFC_KEY, 59, 30, 9).is_err());
+    }
+
+    #[test]
+    fn test_otpauth_uri() {
+        let uri = otpauth_uri("JBSWY3DPEHPK3PXP", "ACME Co", "svc@example.com", 6, 30).unwrap();
+        assert_eq!(
+            uri,
+            "otpauth://totp/ACME%20Co:svc%40example.com?secret=JBSWY3DPEHPK3PXP&issuer=ACME%20Co&algorithm=SHA1&digits=6&period=30"
+        );
+        assert!(otpauth_uri("JBSWY3DP", "a:b", "c", 6, 30).is_err());
+        assert!(otpauth_uri("JBSWY3DP", "a", "", 6, 30).is_err());
+    }
+}
@@ -0,0 +1,413 @@
+//! Wordlists for the `passphrase` style and `w`/`W` pattern placeholders.
+//!
+//! A `WordlistSource` is either one of the lists compiled into the binary
+//! (`eff-large`, `eff-short1`, `eff-short2`) or a path to a newline-delimited
+//! file. The built-in lists are the EFF Diceware lists, embedded with
+//! `include_str!` from `assets/wordlists/` in their published
+//! `<dice number><TAB><word>` format.
+//!
+//! When no source is configured, `eff-large` (7776 words, ~12.9 bits/word) is
+//! used. A build whose assets hold no words reports every built-in list,
+//! including the default, as unavailable rather than substituting a weaker one.
+
+use std::collections::{BTreeSet, HashSet};
+use std::fmt;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::str::FromStr;
+
+use crate::error::GenixError;
+
+const EFF_LARGE: &str = include_str!("../assets/wordlists/eff_large_wordlist.txt");
+const EFF_SHORT_1: &str = include_str!("../assets/wordlists/eff_short_wordlist_1.txt");
+const EFF_SHORT_2: &str = include_str!("../assets/wordlists/eff_short_wordlist_2_0.txt");
+
+/// A wordlist compiled into the binary.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BuiltinWordlist {
+    /// EFF large list: 7776 words, five dice per word.
+    EffLarge,
+    /// EFF short list 1: 1296 short words, four dice per word.
+    EffShort1,
+    /// EFF short list 2: 1296 words with unique three-letter prefixes.
+    EffShort2,
+}
+
+impl BuiltinWordlist {
+    /// Every built-in list, in the order shown in help text.
+    pub const ALL: [BuiltinWordlist; 3] = [
+        BuiltinWordlist::EffLarge,
+        BuiltinWordlist::EffShort1,
+        BuiltinWordlist::EffShort2,
+    ];
+
+    /// The command-line name of the list.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            BuiltinWordlist::EffLarge => "eff-large",
+            BuiltinWordlist::EffShort1 => "eff-short1",
+            BuiltinWordlist::EffShort2 => "eff-short2",
+        }
+    }
+
+    /// The embedded file contents.
+    fn text(self) -> &'static str {
+        match self {
+            BuiltinWordlist::EffLarge => EFF_LARGE,
+            BuiltinWordlist::EffShort1 => EFF_SHORT_1,
+            BuiltinWordlist::EffShort2 => EFF_SHORT_2,
+        }
+    }
+
+    /// The words of the list, with dice numbers stripped.
+    ///
+    /// # Errors
+    /// Returns `GenixError::WordlistUnavailable` if the asset was built
+    /// without any words (see `assets/wordlists/`).
+    pub fn words(self) -> Result<Vec<String>, GenixError> {
+        let words: Vec<String> = self
+            .text()
+            .lines()
+            .filter_map(|line| numbered_line(line).map(|(_, word)| word.to_string()))
+            .collect();
+        if words.is_empty() {
+            return Err(GenixError::WordlistUnavailable(self.as_str().to_string()));
+        }
+        Ok(words)
+    }
+}
+
+impl fmt::Display for BuiltinWordlist {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for BuiltinWordlist {
+    type Err = GenixError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        BuiltinWordlist::ALL
+            .into_iter()
+            .find(|list| list.as_str() == s)
+            .ok_or_else(|| GenixError::UnknownWordlist(s.to_string()))
+    }
+}
+
+/// Where passphrase words come from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WordlistSource {
+    /// A list compiled into the binary.
+    Builtin(BuiltinWordlist),
+    /// A newline-delimited file, one word per line.
+    File(String),
+}
+
+impl Default for WordlistSource {
+    fn default() -> Self {
+        WordlistSource::Builtin(BuiltinWordlist::EffLarge)
+    }
+}
+
+impl From<&str> for WordlistSource {
+    /// Built-in list names take precedence; anything else is a file path.
+    fn from(s: &str) -> Self {
+        match s.parse() {
+            Ok(list) => WordlistSource::Builtin(list),
+            Err(_) => WordlistSource::File(s.to_string()),
+        }
+    }
+}
+
+impl From<String> for WordlistSource {
+    fn from(s: String) -> Self {
+        WordlistSource::from(s.as_str())
+    }
+}
+
+impl fmt::Display for WordlistSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WordlistSource::Builtin(list) => write!(f, "{}", list),
+            WordlistSource::File(path) => f.write_str(path),
+        }
+    }
+}
+
+/// Load the words of `source`, or of the default list when `source` is `None`.
+///
+/// # Errors
+/// Returns `GenixError::WordlistIo` when a file cannot be opened and
+/// `GenixError::WordlistUnavailable` when the requested (or default) built-in
+/// list was not vendored.
+pub fn load_wordlist(source: Option<&WordlistSource>) -> Result<Vec<String>, GenixError> {
+    match source {
+        Some(WordlistSource::Builtin(list)) => list.words(),
+        Some(WordlistSource::File(path)) => {
+            let file = File::open(path).map_err(|source| GenixError::WordlistIo {
+                path: path.clone(),
+                source,
+            })?;
+            let reader = BufReader::new(file);
+            Ok(reader
+                .lines()
+                .map_while(Result::ok)
+                .filter_map(|line| numbered_line(&line).map(|(_, word)| word.to_string()))
+                .collect())
+        }
+        None => BuiltinWordlist::EffLarge.words(),
+    }
+}
+
+/// Load a numbered Diceware list (`11111\tabacus` per line) as
+/// `(dice number, word)` pairs, from `source` or the default `eff-large`.
+///
+/// # Errors
+/// Returns `GenixError::WordlistIo` or `GenixError::WordlistUnavailable` like
+/// `load_wordlist`, and `GenixError::InvalidDiceware` if a line has no dice
+/// number.
+pub fn load_numbered_wordlist(
+    source: Option<&WordlistSource>,
+) -> Result<Vec<(String, String)>, GenixError> {
+    let (text, builtin) = match source {
+        Some(WordlistSource::File(path)) => {
+            let text = std::fs::read_to_string(path).map_err(|source| GenixError::WordlistIo {
+                path: path.clone(),
+                source,
+            })?;
+            (text, None)
+        }
+        Some(WordlistSource::Builtin(list)) => (list.text().to_string(), Some(*list)),
+        None => (
+            BuiltinWordlist::EffLarge.text().to_string(),
+            Some(BuiltinWordlist::EffLarge),
+        ),
+    };
+    let mut entries = Vec::new();
+    for line in text.lines() {
+        match numbered_line(line) {
+            Some((Some(number), word)) => entries.push((number.to_string(), word.to_string())),
+            Some((None, word)) => {
+                return Err(GenixError::InvalidDiceware(format!(
+                    "wordlist line without a dice number: {}",
+                    word
+                )));
+            }
+            None => {}
+        }
+    }
+    if entries.is_empty()
+        && let Some(list) = builtin
+    {
+        return Err(GenixError::WordlistUnavailable(list.as_str().to_string()));
+    }
+    Ok(entries)
+}
+
+/// Smallest list `strict` mode accepts: 10 bits per word.
+pub const MIN_STRICT_WORDS: usize = 1024;
+
+/// Quality report for a wordlist, produced by `analyze`.
+///
+/// Words are compared case-insensitively, matching how passphrases are
+/// capitalized and estimated.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WordlistReport {
+    /// Non-empty lines in the list.
+    pub total: usize,
+    /// Distinct words.
+    pub unique: usize,
+    /// Words that appear more than once (each reported once).
+    pub duplicates: Vec<String>,
+    /// Pairs `(prefix, word)` where `prefix` is a proper prefix of `word`,
+    /// so concatenating without a separator can be ambiguous.
+    pub prefix_violations: Vec<(String, String)>,
+    /// Words containing non-ASCII characters, which may be confusable.
+    pub non_ascii: Vec<String>,
+    /// Shortest word length, in characters.
+    pub min_len: usize,
+    /// Average word length, in characters.
+    pub avg_len: f64,
+    /// Longest word length, in characters.
+    pub max_len: usize,
+    /// Every character used, sorted.
+    pub charset: String,
+    /// `log2(unique)`: the entropy one uniformly chosen word actually carries.
+    pub bits_per_word: f64,
+}
+
+impl WordlistReport {
+    /// Reasons the list fails strict checks. Prefix violations only count
+    /// when `separator` is empty, since any separator makes concatenations
+    /// unambiguous.
+    pub fn problems(&self, separator: &str) -> Vec<String> {
+        let mut problems = Vec::new();
+        if self.unique < MIN_STRICT_WORDS {
+            problems.push(format!(
+                "only {} unique words, at least {} required",
+                self.unique, MIN_STRICT_WORDS
+            ));
+        }
+        if !self.duplicates.is_empty() {
+            problems.push(format!("{} duplicate words", self.duplicates.len()));
+        }
+        if !self.non_ascii.is_empty() {
+            problems.push(format!(
+                "{} words with non-ASCII characters",
+                self.non_ascii.len()
+            ));
+        }
+        if separator.is_empty() && !self.prefix_violations.is_empty() {
+            problems.push(format!(
+                "{} words are prefixes of other words",
+                self.prefix_violations.len()
+            ));
+        }
+        problems
+    }
+}
+
+/// Analyze `words` for duplicates, prefix-code violations, non-ASCII
+/// characters and length statistics.
+pub fn analyze(words: &[String]) -> WordlistReport {
+    let mut seen = HashSet::new();
+    let mut duplicates = Vec::new();
+    let mut unique: Vec<String> = Vec::new();
+    for word in words {
+        let key = word.to_lowercase();
+        if seen.insert(key.clone()) {
+            unique.push(key);
+        } else if !duplicates.contains(&key) {
+            duplicates.push(key);
+        }
+    }
+    unique.sort();
+    // in sorted order the words extending a word form a run right after it
+    let mut prefix_violations = Vec::new();
+    for (i, prefix) in unique.iter().enumerate() {
+        for word in unique[i + 1..]
+            .iter()
+            .take_while(|w| w.starts_with(prefix.as_str()))
+        {
+            prefix_violations.push((prefix.clone(), word.clone()));
+        }
+    }
+    let non_ascii = unique.iter().filter(|w| !w.is_ascii()).cloned().collect();
+
+    let lengths: Vec<usize> = unique.iter().map(|w| w.chars().count()).collect();
+    let charset: BTreeSet<char> = words.iter().flat_map(|w| w.chars()).collect();
+    WordlistReport {
+        total: words.len(),
+        unique: unique.len(),
+        duplicates,
+        prefix_violations,
+        non_ascii,
+        min_len: lengths.iter().copied().min().unwrap_or(0),
+        avg_len: if lengths.is_empty() {
+            0.0
+        } else {
+            lengths.iter().sum::<usize>() as f64 / lengths.len() as f64
+        },
+        max_len: lengths.iter().copied().max().unwrap_or(0),
+        charset: charset.into_iter().collect(),
+        bits_per_word: if unique.is_empty() {
+            0.0
+        } else {
+            (unique.len() as f64).log2()
+        },
+    }
+}
+
+/// Split one wordlist line into its optional dice number and word
+/// (`11111\tabacus`), skipping blank and `#` comment lines.
+fn numbered_line(line: &str) -> Option<(Option<&str>, &str)> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+    match line.split_once(char::is_whitespace) {
+        Some((number, word)) if number.chars().all(|c| c.is_ascii_digit()) => {
+            Some((Some(number), word.trim()))
+        }
+        _ => Some((None, line)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_source_from_name_or_path() {
+        assert_eq!(
+            WordlistSource::from("eff-short2"),
+            WordlistSource::Builtin(BuiltinWordlist::EffShort2)
+        );
+        assert_eq!(
+            WordlistSource::from("words.txt"),
+            WordlistSource::File("words.txt".into())
+        );
+        for list in BuiltinWordlist::ALL {
+            assert_eq!(
+                WordlistSource::from(list.to_string()).to_string(),
+                list.as_str()
+            );
+        }
+    }
+
+    #[test]
+    fn test_numbered_line() {
+        assert_eq!(
+            numbered_line("11111\tabacus"),
+            Some((Some("11111"), "abacus"))
+        );
+        assert_eq!(numbered_line("  zoom  "), Some((None, "zoom")));
+        assert_eq!(numbered_line("# comment"), None);
+        assert_eq!(numbered_line(""), None);
+    }
+
+    #[test]
+    fn test_builtin_lists_are_complete() {
+        for (list, size) in BuiltinWordlist::ALL.into_iter().zip([7776, 1296, 1296]) {
+            let words = list.words().unwrap();
+            assert_eq!(words.len(), size, "{}", list);
+            assert_eq!(analyze(&words).unique, size, "{}", list);
+            let numbered = load_numbered_wordlist(Some(&WordlistSource::Builtin(list))).unwrap();
+            assert_eq!(numbered.len(), size, "{}", list);
+        }
+        assert_eq!(load_wordlist(None).unwrap().len(), 7776);
+    }
+
+    #[test]
+    fn test_analyze_reports_problems() {
+        let words: Vec<String> = ["sun", "Sunday", "moon", "sun", "café", "star"]
+            .iter()
+            .map(|w| w.to_string())
+            .collect();
+        let report = analyze(&words);
+        assert_eq!((report.total, report.unique), (6, 5));
+        assert_eq!(report.duplicates, vec!["sun"]);
+        assert_eq!(
+            report.prefix_violations,
+            vec![("sun".to_string(), "sunday".to_string())]
+        );
+        assert_eq!(report.non_ascii, vec!["café"]);
+        assert_eq!((report.min_len, report.max_len), (3, 6));
+        assert!((report.bits_per_word - 5f64.log2()).abs() < 1e-9);
+        assert_eq!(report.problems("-").len(), 3);
+        assert_eq!(report.problems("").len(), 4);
+    }
+
+    #[test]
+    fn test_prefix_violations_cover_every_extension() {
+        let words: Vec<String> = ["abd", "ab", "abc", "b"]
+            .iter()
+            .map(|w| w.to_string())
+            .collect();
+        let pair = |a: &str, b: &str| (a.to_string(), b.to_string());
+        assert_eq!(
+            analyze(&words).prefix_violations,
+            vec![pair("ab", "abc"), pair("ab", "abd")]
+        );
+    }
+}
@@ -1,9 +1,75 @@
+use std::process::Command;
+
+use genix_lib::generate::{GeneratorConfig, Style, generate_many, seeded_rng};
+
 #[test]
 fn integration_generate_and_entropy() {
     // Generate some items and verify sanity and entropy estimation
-    let res =
-        genix_lib::generate::generate_many("random", 32, 2, None, false, None).expect("generate");
+    let config = GeneratorConfig::new(Style::Random).length(32);
+    let res = generate_many(&config, 2).expect("generate");
     assert_eq!(res.len(), 2);
-    let e = genix_lib::entropy::estimate_entropy_for_str(&res[0], "random").expect("entropy");
+    let e = genix_lib::entropy::estimate_entropy_for_str(&res[0], Style::Random).expect("entropy");
     assert!(e > 0.0);
 }
+
+#[test]
+fn integration_seeded_output_is_reproducible() {
+    let expected = [
+        (Style::Random, "XcINbP"),
+        (Style::Pin, "230131"),
+        (Style::Hex, "cf4e8c4274e8"),
+        (Style::Base64, "z06MQnTo"),
+        (Style::Passphrase, "foxtrot-grape-banana-delta-grape-delta"),
+    ];
+    for (style, want) in expected {
+        let rng = seeded_rng("0123456789abcdef").expect("seed");
+        // pin the wordlist so the expectation does not depend on the default
+        let mut generator = GeneratorConfig::new(style)
+            .length(6)
+            .wordlist("assets/eff_sample.txt")
+            .build_with_rng(rng)
+            .expect("build");
+        assert_eq!(generator.generate_one().unwrap(), want, "style {}", style);
+    }
+}
+
+/// Exit status of the `genix` binary run with `args`.
+fn genix_succeeds(args: &[&str]) -> bool {
+    Command::new(env!("CARGO_BIN_EXE_genix"))
+        .args(args)
+        .output()
+        .expect("run genix")
+        .status
+        .success()
+}
+
+#[test]
+fn integration_check_rejects_bad_tokens() {
+    let config = GeneratorConfig::new(Style::Token);
+    let token = generate_many(&config, 1).expect("generate").remove(0);
+    for command in ["check", "profile"] {
+        assert!(genix_succeeds(&[command, "--style", "token", &token]));
+        let truncated = &token[..token.len() - 1];
+        assert!(!genix_succeeds(&[command, "--style", "token", truncated]));
+    }
+}
+
+#[test]
+fn integration_check_rejects_bad_mnemonics() {
+    let valid = genix_lib::bip39::mnemonic_from_entropy(&[0u8; 16]).expect("mnemonic");
+    assert!(genix_succeeds(&["check", "--style", "bip39", &valid]));
+    // twelve "abandon"s fail the checksum; two words fail the word count
+    let bad_checksum = ["abandon"; 12].join(" ");
+    assert!(!genix_succeeds(&[
+        "check",
+        "--style",
+        "bip39",
+        &bad_checksum
+    ]));
+    assert!(!genix_succeeds(&[
+        "check",
+        "--style",
+        "bip39",
+        "abandon abandon"
+    ]));
+}